@@ -0,0 +1,582 @@
+use super::{DepositOp, VaultAccounts, WithdrawOp, YieldAdapter};
+use crate::{
+    errors::ErrorCode, DepositResult, Deposited, FeesAccrued, FeesClaimed, Rounding, Treasury,
+    Vault, WithdrawResult, Withdrawn, REDEEM_ALL,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    burn_checked, close_account, mint_to, transfer_checked, BurnChecked, CloseAccount, Mint,
+    MintTo, TokenAccount, TokenInterface, TransferChecked,
+};
+
+/// Mints the management fee owed since the last accrual and the performance
+/// fee owed at `total_assets` to the fee recipient, and raises the
+/// high-water mark. Runs ahead of every deposit and withdraw so they are
+/// priced net of fees. Returns an event when a fee was minted.
+pub fn accrue_fees<'info>(
+    vault: &mut Account<'info, Vault>,
+    lp_token: &mut InterfaceAccount<'info, Mint>,
+    fee_recipient_lp_ata: Option<&mut InterfaceAccount<'info, TokenAccount>>,
+    token_program_2022: &Interface<'info, TokenInterface>,
+    total_assets: u64,
+) -> Result<Option<FeesAccrued>> {
+    let now = Clock::get()?.unix_timestamp;
+    let management_fee_lp = vault.management_fee(lp_token.supply, now)?;
+    let lp_supply = lp_token
+        .supply
+        .checked_add(management_fee_lp)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let fee = vault.performance_fee(total_assets, lp_supply)?;
+    let fee_lp = management_fee_lp
+        .checked_add(fee.fee_lp)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    vault.advance_fee_accrual(management_fee_lp, lp_token.supply, now);
+    vault.high_water_mark = fee.high_water_mark;
+    vault.total_assets = total_assets;
+    if fee_lp == 0 {
+        return Ok(None);
+    }
+
+    let fee_recipient_lp_ata = fee_recipient_lp_ata.ok_or(ErrorCode::FeeRecipientMissing)?;
+    let vault_id = vault.vault_id;
+    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[vault.bump]];
+    mint_to(
+        CpiContext::new_with_signer(
+            token_program_2022.to_account_info(),
+            MintTo {
+                mint: lp_token.to_account_info(),
+                to: fee_recipient_lp_ata.to_account_info(),
+                authority: vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        fee_lp,
+    )?;
+    lp_token.reload()?;
+    fee_recipient_lp_ata.reload()?;
+
+    Ok(Some(FeesAccrued {
+        vault: vault.key(),
+        fee_recipient: vault.fee_recipient,
+        management_fee_lp,
+        performance_fee_lp: fee.fee_lp,
+        share_price: vault.share_price(lp_token.supply)?,
+        high_water_mark: fee.high_water_mark,
+    }))
+}
+
+fn accrue<'info>(
+    accounts: &mut VaultAccounts<'_, 'info>,
+    adapter: &impl YieldAdapter<'info>,
+) -> Result<Option<FeesAccrued>> {
+    let total_assets = adapter.total_assets(accounts)?;
+    accrue_fees(
+        accounts.vault,
+        accounts.lp_token,
+        accounts.fee_recipient_lp_ata.as_deref_mut(),
+        accounts.token_program_2022,
+        total_assets,
+    )
+}
+
+/// Pays `fee` in underlying from `from` to the vault's fee token account.
+fn pay_fee<'info>(
+    accounts: &VaultAccounts<'_, 'info>,
+    from: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    signer_seeds: &[&[&[u8]]],
+    fee: u64,
+) -> Result<()> {
+    if fee == 0 {
+        return Ok(());
+    }
+
+    let fee_token_account = accounts
+        .fee_token_account
+        .ok_or(ErrorCode::FeeRecipientMissing)?;
+    transfer_checked(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                from,
+                mint: accounts.token.to_account_info(),
+                to: fee_token_account.to_account_info(),
+                authority,
+            },
+            signer_seeds,
+        ),
+        fee,
+        accounts.token.decimals,
+    )
+}
+
+/// Stages the signer's underlying in the vault, routes it into the protocol,
+/// refunds whatever the protocol left unspent and mints internal LP at the
+/// vault's share price, rounded down, or exactly the LP a mint asks for. The
+/// entry fee is paid on top of the underlying deposited, within `assets` or
+/// `max_assets`. `op` is denominated in internal LP. Returns the events for
+/// the caller to emit.
+pub fn deposit<'info>(
+    accounts: &mut VaultAccounts<'_, 'info>,
+    adapter: &impl YieldAdapter<'info>,
+    op: DepositOp,
+) -> Result<(Option<FeesAccrued>, Deposited, DepositResult)> {
+    let fees = accrue(accounts, adapter)?;
+    let vault_id = accounts.vault.vault_id;
+    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[accounts.vault.bump]];
+
+    let lp_supply_before = accounts.lp_token.supply;
+    let amount_token_before = accounts.vault_token_ata.amount;
+    let amount_shares_before = accounts.vault_shares_ata.amount;
+
+    let (staged, min_lp, protocol_op) = match op {
+        DepositOp::Deposit { assets, min_shares } => {
+            require!(assets > 0, ErrorCode::InvalidAmount);
+            let assets = accounts.vault.net_of_entry_fee(accounts.config, assets)?;
+            (
+                assets,
+                min_shares,
+                DepositOp::Deposit {
+                    assets,
+                    min_shares: 0,
+                },
+            )
+        }
+        DepositOp::Mint { shares, max_assets } => {
+            require!(shares > 0, ErrorCode::InvalidAmount);
+            // An empty vault mints one LP per underlying unit, which protocol
+            // shares are always worth at least.
+            let protocol_shares = if lp_supply_before == 0 {
+                shares
+            } else {
+                Vault::shares_for_lp(shares, amount_shares_before, lp_supply_before, Rounding::Up)?
+            };
+            let max_assets = accounts
+                .vault
+                .net_of_entry_fee(accounts.config, max_assets)?;
+            (
+                max_assets,
+                shares,
+                DepositOp::Mint {
+                    shares: protocol_shares,
+                    max_assets,
+                },
+            )
+        }
+    };
+
+    let vault_assets = accounts.vault.total_assets;
+    let user_assets = Vault::assets_for_lp(
+        accounts.signer_lp_ata.amount,
+        vault_assets,
+        lp_supply_before,
+    )?;
+    accounts
+        .vault
+        .check_deposit_caps(vault_assets, user_assets, staged)?;
+
+    transfer_checked(
+        CpiContext::new(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: accounts.signer_token_ata.to_account_info(),
+                mint: accounts.token.to_account_info(),
+                to: accounts.vault_token_ata.to_account_info(),
+                authority: accounts.signer.to_account_info(),
+            },
+        ),
+        staged,
+        accounts.token.decimals,
+    )?;
+
+    adapter.deposit(accounts, protocol_op, vault_seeds)?;
+
+    accounts.vault_token_ata.reload()?;
+    let refunded = accounts
+        .vault_token_ata
+        .amount
+        .checked_sub(amount_token_before)
+        .ok_or(ErrorCode::MathOverflow)?;
+    if refunded > 0 {
+        transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.vault_token_ata.to_account_info(),
+                    mint: accounts.token.to_account_info(),
+                    to: accounts.signer_token_ata.to_account_info(),
+                    authority: accounts.vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            refunded,
+            accounts.token.decimals,
+        )?;
+    }
+    let assets_in = staged
+        .checked_sub(refunded)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let fee = accounts.vault.entry_fee(accounts.config, assets_in)?;
+    pay_fee(
+        accounts,
+        accounts.signer_token_ata.to_account_info(),
+        accounts.signer.to_account_info(),
+        &[],
+        fee,
+    )?;
+
+    accounts.vault_shares_ata.reload()?;
+    let shares_received = accounts
+        .vault_shares_ata
+        .amount
+        .checked_sub(amount_shares_before)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let lp_value = if lp_supply_before == 0 {
+        adapter.shares_to_assets(shares_received)?
+    } else {
+        Vault::lp_for_shares(
+            shares_received,
+            amount_shares_before,
+            lp_supply_before,
+            Rounding::Down,
+        )?
+    };
+    require!(lp_value > 0, ErrorCode::InvalidAmount);
+    require!(lp_value >= min_lp, ErrorCode::SlippageExceeded);
+    // A mint credits exactly the LP asked for. Shares received beyond it
+    // stay in the vault.
+    let lp_minted = match op {
+        DepositOp::Deposit { .. } => lp_value,
+        DepositOp::Mint { shares, .. } => shares,
+    };
+
+    mint_to(
+        CpiContext::new_with_signer(
+            accounts.token_program_2022.to_account_info(),
+            MintTo {
+                mint: accounts.lp_token.to_account_info(),
+                to: accounts.signer_lp_ata.to_account_info(),
+                authority: accounts.vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        lp_minted,
+    )?;
+
+    accounts.lp_token.reload()?;
+    Vault::check_backing(
+        amount_shares_before,
+        lp_supply_before,
+        accounts.vault_shares_ata.amount,
+        accounts.lp_token.supply,
+    )?;
+    accounts.vault.total_assets = adapter.total_assets(accounts)?;
+
+    Ok((
+        fees,
+        Deposited {
+            user: accounts.signer.key(),
+            vault: accounts.vault.key(),
+            underlying_in: assets_in,
+            external_shares_received: shares_received,
+            internal_lp_minted: lp_minted,
+            refunded,
+            fee,
+        },
+        DepositResult {
+            assets_in,
+            lp_minted,
+            share_price: accounts.vault.share_price(accounts.lp_token.supply)?,
+        },
+    ))
+}
+
+/// Redeems the protocol shares backing the signer's internal LP, burns that
+/// LP at the vault's share price, rounded up, and pays the underlying out
+/// net of the exit fee. Asset bounds in `op` apply to the net amount.
+/// `op` is denominated in internal LP. `REDEEM_ALL` redeems the signer's
+/// whole LP balance, after which `close_lp_account` can close it.
+/// `signer_seeds` sign for a PDA `signer`, and are empty for wallets.
+pub fn withdraw<'info>(
+    accounts: &mut VaultAccounts<'_, 'info>,
+    adapter: &impl YieldAdapter<'info>,
+    op: WithdrawOp,
+    close_lp_account: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(Option<FeesAccrued>, Withdrawn, WithdrawResult)> {
+    let fees = accrue(accounts, adapter)?;
+    let (event, result) = withdraw_accrued(accounts, adapter, op, close_lp_account, signer_seeds)?;
+    Ok((fees, event, result))
+}
+
+/// `withdraw` once fees are accrued.
+fn withdraw_accrued<'info>(
+    accounts: &mut VaultAccounts<'_, 'info>,
+    adapter: &impl YieldAdapter<'info>,
+    op: WithdrawOp,
+    close_lp_account: bool,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<(Withdrawn, WithdrawResult)> {
+    let vault_id = accounts.vault.vault_id;
+    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[accounts.vault.bump]];
+
+    let lp_balance = accounts.signer_lp_ata.amount;
+    let lp_supply_before = accounts.lp_token.supply;
+    let amount_token_before = accounts.vault_token_ata.amount;
+    let amount_shares_before = accounts.vault_shares_ata.amount;
+    require!(lp_supply_before > 0, ErrorCode::InvalidAmount);
+
+    let op = match op {
+        WithdrawOp::Withdraw { assets, max_shares } => {
+            require!(assets > 0, ErrorCode::InvalidAmount);
+            WithdrawOp::Withdraw {
+                assets,
+                max_shares: max_shares.min(lp_balance),
+            }
+        }
+        WithdrawOp::Redeem { shares, min_assets } => {
+            let shares = if shares == REDEEM_ALL {
+                lp_balance
+            } else {
+                shares
+            };
+            require!(shares > 0, ErrorCode::InvalidAmount);
+            WithdrawOp::Redeem { shares, min_assets }
+        }
+    };
+    // `redeem_shares` are the protocol shares a redeem must consume exactly.
+    let (protocol_op, redeem_shares) = match op {
+        WithdrawOp::Withdraw { assets, max_shares } => (
+            WithdrawOp::Withdraw {
+                assets: accounts.vault.gross_of_exit_fee(accounts.config, assets)?,
+                max_shares: Vault::shares_for_lp(
+                    max_shares,
+                    amount_shares_before,
+                    lp_supply_before,
+                    Rounding::Down,
+                )?,
+            },
+            0,
+        ),
+        WithdrawOp::Redeem { shares, min_assets } => {
+            let protocol_shares = Vault::shares_for_lp(
+                shares,
+                amount_shares_before,
+                lp_supply_before,
+                Rounding::Down,
+            )?;
+            (
+                WithdrawOp::Redeem {
+                    shares: protocol_shares,
+                    min_assets,
+                },
+                protocol_shares,
+            )
+        }
+    };
+
+    adapter.withdraw(accounts, protocol_op, vault_seeds)?;
+
+    accounts.vault_token_ata.reload()?;
+    let assets_out = accounts
+        .vault_token_ata
+        .amount
+        .checked_sub(amount_token_before)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    // The treasury pays no fees to itself.
+    let fee = if accounts.signer.key() == accounts.vault.fee_recipient {
+        0
+    } else {
+        accounts.vault.exit_fee(accounts.config, assets_out)?
+    };
+    let assets_out = assets_out - fee;
+
+    accounts.vault_shares_ata.reload()?;
+    let shares_redeemed = amount_shares_before
+        .checked_sub(accounts.vault_shares_ata.amount)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let lp_burned = match op {
+        WithdrawOp::Withdraw { assets, max_shares } => {
+            let lp_burned = Vault::lp_for_shares(
+                shares_redeemed,
+                amount_shares_before,
+                lp_supply_before,
+                Rounding::Up,
+            )?;
+            require!(assets_out >= assets, ErrorCode::SlippageExceeded);
+            require!(lp_burned <= max_shares, ErrorCode::SlippageExceeded);
+            lp_burned
+        }
+        WithdrawOp::Redeem { shares, min_assets } => {
+            require!(
+                shares_redeemed == redeem_shares,
+                ErrorCode::SlippageExceeded
+            );
+            require!(assets_out >= min_assets, ErrorCode::SlippageExceeded);
+            shares
+        }
+    };
+
+    burn_checked(
+        CpiContext::new_with_signer(
+            accounts.token_program_2022.to_account_info(),
+            BurnChecked {
+                mint: accounts.lp_token.to_account_info(),
+                from: accounts.signer_lp_ata.to_account_info(),
+                authority: accounts.signer.to_account_info(),
+            },
+            signer_seeds,
+        ),
+        lp_burned,
+        accounts.lp_token.decimals,
+    )?;
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: accounts.vault_token_ata.to_account_info(),
+                mint: accounts.token.to_account_info(),
+                to: accounts.signer_token_ata.to_account_info(),
+                authority: accounts.vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        assets_out,
+        accounts.token.decimals,
+    )?;
+    pay_fee(
+        accounts,
+        accounts.vault_token_ata.to_account_info(),
+        accounts.vault.to_account_info(),
+        &[vault_seeds],
+        fee,
+    )?;
+
+    if close_lp_account {
+        close_account(CpiContext::new_with_signer(
+            accounts.token_program_2022.to_account_info(),
+            CloseAccount {
+                account: accounts.signer_lp_ata.to_account_info(),
+                destination: accounts.signer.to_account_info(),
+                authority: accounts.signer.to_account_info(),
+            },
+            signer_seeds,
+        ))?;
+    }
+
+    accounts.lp_token.reload()?;
+    Vault::check_backing(
+        amount_shares_before,
+        lp_supply_before,
+        accounts.vault_shares_ata.amount,
+        accounts.lp_token.supply,
+    )?;
+    accounts.vault.total_assets = adapter.total_assets(accounts)?;
+
+    Ok((
+        Withdrawn {
+            user: accounts.signer.key(),
+            vault: accounts.vault.key(),
+            lp_burned,
+            external_shares_redeemed: shares_redeemed,
+            underlying_out: assets_out,
+            fee,
+        },
+        WithdrawResult {
+            lp_burned,
+            assets_out,
+            share_price: accounts.vault.share_price(accounts.lp_token.supply)?,
+        },
+    ))
+}
+
+/// Mints fees owed to the treasury, redeems all the internal LP it holds
+/// and pays its whole underlying balance out to `destinations` by its split
+/// weights. `accounts.signer` is the treasury.
+pub fn claim_fees<'info>(
+    accounts: &mut VaultAccounts<'_, 'info>,
+    adapter: &impl YieldAdapter<'info>,
+    treasury: &Treasury,
+    treasury_seeds: &[&[u8]],
+    destinations: &[AccountInfo<'info>],
+) -> Result<(Option<FeesAccrued>, FeesClaimed)> {
+    require_keys_eq!(
+        accounts.signer.key(),
+        accounts.vault.fee_recipient,
+        ErrorCode::FeeRecipientMissing
+    );
+
+    let total_assets = adapter.total_assets(accounts)?;
+    let fees = accrue_fees(
+        accounts.vault,
+        accounts.lp_token,
+        Some(&mut *accounts.signer_lp_ata),
+        accounts.token_program_2022,
+        total_assets,
+    )?;
+
+    let lp_redeemed = accounts.signer_lp_ata.amount;
+    let mut amount = accounts.signer_token_ata.amount;
+    if lp_redeemed > 0 {
+        // Fees were just accrued to the treasury, so it redeems them without
+        // accruing again.
+        let (_, result) = withdraw_accrued(
+            accounts,
+            adapter,
+            WithdrawOp::Redeem {
+                shares: lp_redeemed,
+                min_assets: 0,
+            },
+            false,
+            &[treasury_seeds],
+        )?;
+        amount = amount
+            .checked_add(result.assets_out)
+            .ok_or(ErrorCode::MathOverflow)?;
+    }
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    let amounts = treasury.split_amounts(amount)?;
+    require!(
+        destinations.len() == amounts.len(),
+        ErrorCode::InvalidFeeSplit
+    );
+    for ((split, destination), share) in treasury.splits.iter().zip(destinations).zip(amounts) {
+        require_keys_eq!(
+            destination.key(),
+            split.destination,
+            ErrorCode::InvalidFeeSplit
+        );
+        if share == 0 {
+            continue;
+        }
+
+        transfer_checked(
+            CpiContext::new_with_signer(
+                accounts.token_program.to_account_info(),
+                TransferChecked {
+                    from: accounts.signer_token_ata.to_account_info(),
+                    mint: accounts.token.to_account_info(),
+                    to: destination.clone(),
+                    authority: accounts.signer.to_account_info(),
+                },
+                &[treasury_seeds],
+            ),
+            share,
+            accounts.token.decimals,
+        )?;
+    }
+
+    Ok((
+        fees,
+        FeesClaimed {
+            vault: accounts.vault.key(),
+            lp_redeemed,
+            amount,
+        },
+    ))
+}
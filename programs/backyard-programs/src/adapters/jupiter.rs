@@ -0,0 +1,201 @@
+use super::{DepositOp, VaultAccounts, WithdrawOp, YieldAdapter};
+use crate::{
+    errors::ErrorCode,
+    lending::{
+        accounts::Lending,
+        cpi::{
+            accounts::{
+                DepositWithMinAmountOut, MintWithMaxAssets, RedeemWithMinAmountOut,
+                WithdrawWithMaxSharesBurn,
+            },
+            deposit_with_min_amount_out, mint_with_max_assets, redeem_with_min_amount_out,
+            withdraw_with_max_shares_burn,
+        },
+    },
+};
+use anchor_lang::prelude::*;
+
+/// Precision of Jupiter Lend exchange prices.
+pub const EXCHANGE_PRICES_PRECISION: u128 = 1_000_000_000_000;
+
+pub fn load_lending(info: &AccountInfo) -> Result<Lending> {
+    require_keys_eq!(*info.owner, crate::lending::ID, ErrorCode::WrongMarket);
+    Lending::try_deserialize(&mut &info.try_borrow_data()?[..])
+        .map_err(|_| ErrorCode::WrongMarket.into())
+}
+
+/// Underlying value of `shares` fTokens at the last recorded exchange price.
+pub fn shares_to_assets(lending: &Lending, shares: u64) -> Result<u64> {
+    let assets = (shares as u128)
+        .checked_mul(lending.token_exchange_price as u128)
+        .ok_or(ErrorCode::MathOverflow)?
+        / EXCHANGE_PRICES_PRECISION;
+    u64::try_from(assets).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Jupiter Lend accounts of a vault operation. `claim_account` is only
+/// needed to withdraw.
+pub struct JupiterLendAdapter<'info> {
+    pub lending_program: AccountInfo<'info>,
+    pub lending_admin: AccountInfo<'info>,
+    pub lending: AccountInfo<'info>,
+    pub f_token_mint: AccountInfo<'info>,
+    pub supply_token_reserves_liquidity: AccountInfo<'info>,
+    pub lending_supply_position_on_liquidity: AccountInfo<'info>,
+    pub rate_model: AccountInfo<'info>,
+    pub jupiter_vault: AccountInfo<'info>,
+    pub claim_account: Option<AccountInfo<'info>>,
+    pub liquidity: AccountInfo<'info>,
+    pub liquidity_program: AccountInfo<'info>,
+    pub rewards_rate_model: AccountInfo<'info>,
+    pub associated_token_program: AccountInfo<'info>,
+    pub system_program: AccountInfo<'info>,
+}
+
+impl<'info> YieldAdapter<'info> for JupiterLendAdapter<'info> {
+    fn shares_to_assets(&self, shares: u64) -> Result<u64> {
+        shares_to_assets(&load_lending(&self.lending)?, shares)
+    }
+
+    fn deposit(
+        &self,
+        accounts: &VaultAccounts<'_, 'info>,
+        op: DepositOp,
+        vault_seeds: &[&[u8]],
+    ) -> Result<()> {
+        let cpi_accounts = DepositWithMinAmountOut {
+            signer: accounts.vault.to_account_info(),
+            depositor_token_account: accounts.vault_token_ata.to_account_info(),
+            recipient_token_account: accounts.vault_shares_ata.to_account_info(),
+            mint: accounts.token.to_account_info(),
+            lending_admin: self.lending_admin.clone(),
+            lending: self.lending.clone(),
+            f_token_mint: self.f_token_mint.clone(),
+            supply_token_reserves_liquidity: self.supply_token_reserves_liquidity.clone(),
+            lending_supply_position_on_liquidity: self.lending_supply_position_on_liquidity.clone(),
+            rate_model: self.rate_model.clone(),
+            vault: self.jupiter_vault.clone(),
+            liquidity: self.liquidity.clone(),
+            liquidity_program: self.liquidity_program.clone(),
+            rewards_rate_model: self.rewards_rate_model.clone(),
+            token_program: accounts.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.clone(),
+            system_program: self.system_program.clone(),
+        };
+        let signer_seeds = &[vault_seeds];
+
+        match op {
+            DepositOp::Deposit { assets, min_shares } => deposit_with_min_amount_out(
+                CpiContext::new_with_signer(
+                    self.lending_program.clone(),
+                    cpi_accounts,
+                    signer_seeds,
+                ),
+                assets,
+                min_shares,
+            ),
+            DepositOp::Mint { shares, max_assets } => mint_with_max_assets(
+                CpiContext::new_with_signer(
+                    self.lending_program.clone(),
+                    MintWithMaxAssets {
+                        signer: cpi_accounts.signer,
+                        depositor_token_account: cpi_accounts.depositor_token_account,
+                        recipient_token_account: cpi_accounts.recipient_token_account,
+                        mint: cpi_accounts.mint,
+                        lending_admin: cpi_accounts.lending_admin,
+                        lending: cpi_accounts.lending,
+                        f_token_mint: cpi_accounts.f_token_mint,
+                        supply_token_reserves_liquidity: cpi_accounts
+                            .supply_token_reserves_liquidity,
+                        lending_supply_position_on_liquidity: cpi_accounts
+                            .lending_supply_position_on_liquidity,
+                        rate_model: cpi_accounts.rate_model,
+                        vault: cpi_accounts.vault,
+                        liquidity: cpi_accounts.liquidity,
+                        liquidity_program: cpi_accounts.liquidity_program,
+                        rewards_rate_model: cpi_accounts.rewards_rate_model,
+                        token_program: cpi_accounts.token_program,
+                        associated_token_program: cpi_accounts.associated_token_program,
+                        system_program: cpi_accounts.system_program,
+                    },
+                    signer_seeds,
+                ),
+                shares,
+                max_assets,
+            )
+            .map(|_| ()),
+        }
+    }
+
+    fn withdraw(
+        &self,
+        accounts: &VaultAccounts<'_, 'info>,
+        op: WithdrawOp,
+        vault_seeds: &[&[u8]],
+    ) -> Result<()> {
+        let cpi_accounts = WithdrawWithMaxSharesBurn {
+            signer: accounts.vault.to_account_info(),
+            owner_token_account: accounts.vault_shares_ata.to_account_info(),
+            recipient_token_account: accounts.vault_token_ata.to_account_info(),
+            lending_admin: self.lending_admin.clone(),
+            lending: self.lending.clone(),
+            mint: accounts.token.to_account_info(),
+            f_token_mint: self.f_token_mint.clone(),
+            supply_token_reserves_liquidity: self.supply_token_reserves_liquidity.clone(),
+            lending_supply_position_on_liquidity: self.lending_supply_position_on_liquidity.clone(),
+            rate_model: self.rate_model.clone(),
+            vault: self.jupiter_vault.clone(),
+            claim_account: self.claim_account.clone().ok_or(ErrorCode::WrongMarket)?,
+            liquidity: self.liquidity.clone(),
+            liquidity_program: self.liquidity_program.clone(),
+            rewards_rate_model: self.rewards_rate_model.clone(),
+            token_program: accounts.token_program.to_account_info(),
+            associated_token_program: self.associated_token_program.clone(),
+            system_program: self.system_program.clone(),
+        };
+        let signer_seeds = &[vault_seeds];
+
+        match op {
+            WithdrawOp::Withdraw { assets, max_shares } => withdraw_with_max_shares_burn(
+                CpiContext::new_with_signer(
+                    self.lending_program.clone(),
+                    cpi_accounts,
+                    signer_seeds,
+                ),
+                assets,
+                max_shares,
+            )
+            .map(|_| ()),
+            WithdrawOp::Redeem { shares, min_assets } => redeem_with_min_amount_out(
+                CpiContext::new_with_signer(
+                    self.lending_program.clone(),
+                    RedeemWithMinAmountOut {
+                        signer: cpi_accounts.signer,
+                        owner_token_account: cpi_accounts.owner_token_account,
+                        recipient_token_account: cpi_accounts.recipient_token_account,
+                        lending_admin: cpi_accounts.lending_admin,
+                        lending: cpi_accounts.lending,
+                        mint: cpi_accounts.mint,
+                        f_token_mint: cpi_accounts.f_token_mint,
+                        supply_token_reserves_liquidity: cpi_accounts
+                            .supply_token_reserves_liquidity,
+                        lending_supply_position_on_liquidity: cpi_accounts
+                            .lending_supply_position_on_liquidity,
+                        rate_model: cpi_accounts.rate_model,
+                        vault: cpi_accounts.vault,
+                        claim_account: cpi_accounts.claim_account,
+                        liquidity: cpi_accounts.liquidity,
+                        liquidity_program: cpi_accounts.liquidity_program,
+                        rewards_rate_model: cpi_accounts.rewards_rate_model,
+                        token_program: cpi_accounts.token_program,
+                        associated_token_program: cpi_accounts.associated_token_program,
+                        system_program: cpi_accounts.system_program,
+                    },
+                    signer_seeds,
+                ),
+                shares,
+                min_assets,
+            ),
+        }
+    }
+}
@@ -0,0 +1,341 @@
+use super::{DepositOp, VaultAccounts, WithdrawOp, YieldAdapter};
+use crate::{
+    errors::ErrorCode,
+    kamino_vault_converted::{
+        accounts::VaultState,
+        cpi::{
+            accounts::{Deposit, Withdraw, WithdrawFromAvailable, WithdrawFromReserveAccounts},
+            deposit, withdraw,
+        },
+    },
+};
+use anchor_lang::{prelude::*, Discriminator};
+use bytemuck::{Pod, Zeroable};
+
+pub const MAX_RESERVES: usize = 25;
+
+/// Leading fields of Kamino's `VaultState`. The full account is too large to
+/// deserialize on-chain, so only the parts we validate against are read.
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct VaultStateHeader {
+    pub vault_admin_authority: Pubkey,
+    pub base_vault_authority: Pubkey,
+    pub base_vault_authority_bump: u64,
+    pub token_mint: Pubkey,
+    pub token_mint_decimals: u64,
+    pub token_vault: Pubkey,
+    pub token_program: Pubkey,
+    pub shares_mint: Pubkey,
+    pub shares_mint_decimals: u64,
+    pub token_available: u64,
+    pub shares_issued: u64,
+    pub available_crank_funds: u64,
+    pub unallocated_weight: u64,
+    pub performance_fee_bps: u64,
+    pub management_fee_bps: u64,
+    pub last_fee_charge_timestamp: u64,
+    pub prev_aum_sf: [u8; 16],
+    pub pending_fees_sf: [u8; 16],
+}
+
+#[repr(C)]
+#[derive(Clone, Copy, Pod, Zeroable)]
+pub struct VaultAllocation {
+    pub reserve: Pubkey,
+    pub ctoken_vault: Pubkey,
+    pub target_allocation_weight: u64,
+    pub token_allocation_cap: u64,
+    pub ctoken_vault_bump: u64,
+    pub config_padding: [u64; 127],
+    pub ctoken_allocation: u64,
+    pub last_invest_slot: u64,
+    pub token_target_allocation_sf: [u8; 16],
+    pub state_padding: [u64; 128],
+}
+
+const HEADER_OFFSET: usize = 8;
+const ALLOCATIONS_OFFSET: usize = HEADER_OFFSET + std::mem::size_of::<VaultStateHeader>();
+const ALLOCATION_SIZE: usize = std::mem::size_of::<VaultAllocation>();
+
+fn check_vault_state(info: &AccountInfo, data: &[u8]) -> Result<()> {
+    require_keys_eq!(
+        *info.owner,
+        crate::kamino_vault_converted::ID,
+        ErrorCode::WrongMarket
+    );
+    require!(
+        data.len() >= ALLOCATIONS_OFFSET + MAX_RESERVES * ALLOCATION_SIZE
+            && data.starts_with(VaultState::DISCRIMINATOR),
+        ErrorCode::WrongMarket
+    );
+    Ok(())
+}
+
+impl VaultStateHeader {
+    pub fn load(info: &AccountInfo) -> Result<Self> {
+        let data = info.try_borrow_data()?;
+        check_vault_state(info, &data)?;
+        Ok(bytemuck::pod_read_unaligned(
+            &data[HEADER_OFFSET..ALLOCATIONS_OFFSET],
+        ))
+    }
+}
+
+impl VaultStateHeader {
+    /// Kamino stores amounts as fixed point numbers with 60 fractional bits.
+    const FRACTION_BITS: u32 = 60;
+
+    /// Assets under management, net of pending fees, as of the last fee charge.
+    pub fn aum(&self) -> u128 {
+        let prev_aum_sf = u128::from_le_bytes(self.prev_aum_sf);
+        let pending_fees_sf = u128::from_le_bytes(self.pending_fees_sf);
+        prev_aum_sf.saturating_sub(pending_fees_sf) >> Self::FRACTION_BITS
+    }
+
+    pub fn shares_to_assets(&self, shares: u64) -> Result<u64> {
+        if self.shares_issued == 0 {
+            return Ok(shares);
+        }
+        let assets = (shares as u128)
+            .checked_mul(self.aum())
+            .ok_or(ErrorCode::MathOverflow)?
+            / self.shares_issued as u128;
+        u64::try_from(assets).map_err(|_| ErrorCode::MathOverflow.into())
+    }
+
+    /// Assets to deposit so that at least `shares` are minted, rounded up and
+    /// padded by `STALE_AUM_BUFFER_BPS` since the recorded AUM only grows
+    /// once Kamino refreshes it inside the deposit.
+    pub fn assets_for_shares(&self, shares: u64) -> Result<u64> {
+        if self.shares_issued == 0 {
+            return Ok(shares);
+        }
+        let assets = ceil_div(
+            (shares as u128)
+                .checked_mul(self.aum())
+                .ok_or(ErrorCode::MathOverflow)?,
+            self.shares_issued as u128,
+        )?;
+        with_buffer(assets)
+    }
+
+    /// Shares to redeem so that at least `assets` are paid out, rounded up
+    /// and padded like `assets_for_shares`.
+    pub fn shares_for_assets(&self, assets: u64) -> Result<u64> {
+        let aum = self.aum();
+        if self.shares_issued == 0 || aum == 0 {
+            return Ok(assets);
+        }
+        let shares = ceil_div(
+            (assets as u128)
+                .checked_mul(self.shares_issued as u128)
+                .ok_or(ErrorCode::MathOverflow)?,
+            aum,
+        )?;
+        with_buffer(shares)
+    }
+}
+
+/// Headroom applied when sizing Kamino operations from the last recorded AUM.
+pub const STALE_AUM_BUFFER_BPS: u128 = 10;
+
+const BPS_DENOMINATOR: u128 = 10_000;
+
+fn ceil_div(numerator: u128, denominator: u128) -> Result<u128> {
+    numerator
+        .checked_add(denominator - 1)
+        .map(|n| n / denominator)
+        .ok_or(ErrorCode::MathOverflow.into())
+}
+
+fn with_buffer(amount: u128) -> Result<u64> {
+    let buffered = ceil_div(
+        amount
+            .checked_mul(BPS_DENOMINATOR + STALE_AUM_BUFFER_BPS)
+            .ok_or(ErrorCode::MathOverflow)?,
+        BPS_DENOMINATOR,
+    )?;
+    u64::try_from(buffered).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Returns the ctoken vault the Kamino vault holds for `reserve`, if the
+/// reserve is part of its allocation strategy.
+pub fn find_ctoken_vault(info: &AccountInfo, reserve: &Pubkey) -> Result<Option<Pubkey>> {
+    let data = info.try_borrow_data()?;
+    check_vault_state(info, &data)?;
+
+    for i in 0..MAX_RESERVES {
+        let start = ALLOCATIONS_OFFSET + i * ALLOCATION_SIZE;
+        let allocation: &VaultAllocation =
+            bytemuck::try_from_bytes(&data[start..start + ALLOCATION_SIZE])
+                .map_err(|_| ErrorCode::WrongMarket)?;
+        if allocation.reserve == *reserve {
+            return Ok(Some(allocation.ctoken_vault));
+        }
+    }
+    Ok(None)
+}
+
+/// Kamino reserve a withdrawal may pull liquidity from when the vault's
+/// available balance falls short.
+pub struct KaminoReserveAccounts<'info> {
+    pub reserve: AccountInfo<'info>,
+    pub ctoken_vault: AccountInfo<'info>,
+    pub instruction_sysvar_account: AccountInfo<'info>,
+    pub lending_market: AccountInfo<'info>,
+    pub lending_market_authority: AccountInfo<'info>,
+    pub reserve_liquidity_supply: AccountInfo<'info>,
+    pub reserve_collateral_mint: AccountInfo<'info>,
+}
+
+/// Kamino kVault accounts of a vault operation. `reserve` is only needed to
+/// withdraw; `remaining_accounts` are forwarded to Kamino untouched.
+pub struct KaminoVaultAdapter<'info> {
+    pub kamino_vault: AccountInfo<'info>,
+    pub vault_state: AccountInfo<'info>,
+    pub token_vault: AccountInfo<'info>,
+    pub base_vault_authority: AccountInfo<'info>,
+    pub shares_mint: AccountInfo<'info>,
+    pub kamino_event_authority: AccountInfo<'info>,
+    pub klend_program: AccountInfo<'info>,
+    pub reserve: Option<KaminoReserveAccounts<'info>>,
+    pub remaining_accounts: Vec<AccountInfo<'info>>,
+}
+
+impl KaminoVaultAdapter<'_> {
+    /// Checks the Kamino accounts against the vault state bound to the vault,
+    /// and the reserve against its allocation strategy.
+    pub fn validate_market(&self) -> Result<()> {
+        let state = VaultStateHeader::load(&self.vault_state)?;
+
+        require_keys_eq!(
+            state.token_vault,
+            self.token_vault.key(),
+            ErrorCode::WrongMarket
+        );
+        require_keys_eq!(
+            state.base_vault_authority,
+            self.base_vault_authority.key(),
+            ErrorCode::WrongMarket
+        );
+        require_keys_eq!(
+            state.shares_mint,
+            self.shares_mint.key(),
+            ErrorCode::WrongMarket
+        );
+
+        if let Some(reserve) = &self.reserve {
+            let ctoken_vault = find_ctoken_vault(&self.vault_state, &reserve.reserve.key())?
+                .ok_or(ErrorCode::WrongMarket)?;
+            require_keys_eq!(
+                ctoken_vault,
+                reserve.ctoken_vault.key(),
+                ErrorCode::WrongMarket
+            );
+        }
+        Ok(())
+    }
+}
+
+impl<'info> YieldAdapter<'info> for KaminoVaultAdapter<'info> {
+    fn shares_to_assets(&self, shares: u64) -> Result<u64> {
+        VaultStateHeader::load(&self.vault_state)?.shares_to_assets(shares)
+    }
+
+    fn deposit(
+        &self,
+        accounts: &VaultAccounts<'_, 'info>,
+        op: DepositOp,
+        vault_seeds: &[&[u8]],
+    ) -> Result<()> {
+        // Kamino only takes an asset amount, so mints are sized from the
+        // recorded AUM and checked against the shares received afterwards.
+        let amount = match op {
+            DepositOp::Deposit { assets, .. } => assets,
+            DepositOp::Mint { shares, max_assets } => VaultStateHeader::load(&self.vault_state)?
+                .assets_for_shares(shares)?
+                .min(max_assets),
+        };
+
+        deposit(
+            CpiContext::new_with_signer(
+                self.kamino_vault.clone(),
+                Deposit {
+                    user: accounts.vault.to_account_info(),
+                    vault_state: self.vault_state.clone(),
+                    token_vault: self.token_vault.clone(),
+                    token_mint: accounts.token.to_account_info(),
+                    base_vault_authority: self.base_vault_authority.clone(),
+                    shares_mint: self.shares_mint.clone(),
+                    user_token_ata: accounts.vault_token_ata.to_account_info(),
+                    user_shares_ata: accounts.vault_shares_ata.to_account_info(),
+                    klend_program: self.klend_program.clone(),
+                    token_program: accounts.token_program.to_account_info(),
+                    shares_token_program: accounts.token_program.to_account_info(),
+                    event_authority: self.kamino_event_authority.clone(),
+                    program: self.kamino_vault.clone(),
+                },
+                &[vault_seeds],
+            )
+            .with_remaining_accounts(self.remaining_accounts.clone()),
+            amount,
+        )
+    }
+
+    fn withdraw(
+        &self,
+        accounts: &VaultAccounts<'_, 'info>,
+        op: WithdrawOp,
+        vault_seeds: &[&[u8]],
+    ) -> Result<()> {
+        let reserve = self.reserve.as_ref().ok_or(ErrorCode::WrongMarket)?;
+        let shares = match op {
+            WithdrawOp::Withdraw { assets, max_shares } => {
+                VaultStateHeader::load(&self.vault_state)?
+                    .shares_for_assets(assets)?
+                    .min(max_shares)
+            }
+            WithdrawOp::Redeem { shares, .. } => shares,
+        };
+
+        withdraw(
+            CpiContext::new_with_signer(
+                self.kamino_vault.clone(),
+                Withdraw {
+                    withdraw_from_available: WithdrawFromAvailable {
+                        user: accounts.vault.to_account_info(),
+                        base_vault_authority: self.base_vault_authority.clone(),
+                        event_authority: self.kamino_event_authority.clone(),
+                        klend_program: self.klend_program.clone(),
+                        program: self.kamino_vault.clone(),
+                        shares_mint: self.shares_mint.clone(),
+                        shares_token_program: accounts.token_program.to_account_info(),
+                        token_mint: accounts.token.to_account_info(),
+                        token_program: accounts.token_program.to_account_info(),
+                        token_vault: self.token_vault.clone(),
+                        user_shares_ata: accounts.vault_shares_ata.to_account_info(),
+                        user_token_ata: accounts.vault_token_ata.to_account_info(),
+                        vault_state: self.vault_state.clone(),
+                    },
+                    withdraw_from_reserve_accounts: WithdrawFromReserveAccounts {
+                        ctoken_vault: reserve.ctoken_vault.clone(),
+                        instruction_sysvar_account: reserve.instruction_sysvar_account.clone(),
+                        lending_market: reserve.lending_market.clone(),
+                        lending_market_authority: reserve.lending_market_authority.clone(),
+                        reserve: reserve.reserve.clone(),
+                        reserve_collateral_mint: reserve.reserve_collateral_mint.clone(),
+                        reserve_collateral_token_program: accounts.token_program.to_account_info(),
+                        reserve_liquidity_supply: reserve.reserve_liquidity_supply.clone(),
+                        vault_state: self.vault_state.clone(),
+                    },
+                    event_authority: self.kamino_event_authority.clone(),
+                    program: self.kamino_vault.clone(),
+                },
+                &[vault_seeds],
+            )
+            .with_remaining_accounts(self.remaining_accounts.clone()),
+            shares,
+        )
+    }
+}
@@ -0,0 +1,90 @@
+pub mod flow;
+pub mod jupiter;
+pub mod kamino;
+
+use crate::{Config, Protocol, Vault};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+/// Underlying value of `shares` external shares of `market`.
+pub fn shares_to_assets(protocol: Protocol, market: &AccountInfo, shares: u64) -> Result<u64> {
+    match protocol {
+        Protocol::JupiterLend => jupiter::shares_to_assets(&jupiter::load_lending(market)?, shares),
+        Protocol::KaminoVault => kamino::VaultStateHeader::load(market)?.shares_to_assets(shares),
+    }
+}
+
+/// Accounts every vault operation touches, whatever the protocol behind it.
+/// `signer` owns the LP and token accounts, and is the vault's treasury
+/// when fees are claimed. `vault_token_ata` stages underlying on its way in
+/// and out of the protocol and `vault_shares_ata` holds the protocol's
+/// shares. `fee_recipient_lp_ata` and `fee_token_account` are only required
+/// when a fee is due. `config` bounds the entry and exit fees charged.
+pub struct VaultAccounts<'a, 'info> {
+    pub signer: &'a AccountInfo<'info>,
+    pub config: &'a Config,
+    pub vault: &'a mut Account<'info, Vault>,
+    pub token: &'a InterfaceAccount<'info, Mint>,
+    pub signer_token_ata: &'a InterfaceAccount<'info, TokenAccount>,
+    pub vault_token_ata: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub vault_shares_ata: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub lp_token: &'a mut InterfaceAccount<'info, Mint>,
+    pub signer_lp_ata: &'a mut InterfaceAccount<'info, TokenAccount>,
+    pub fee_recipient_lp_ata: Option<&'a mut InterfaceAccount<'info, TokenAccount>>,
+    pub fee_token_account: Option<&'a InterfaceAccount<'info, TokenAccount>>,
+    pub token_program: &'a Interface<'info, TokenInterface>,
+    pub token_program_2022: &'a Interface<'info, TokenInterface>,
+}
+
+/// Amounts are internal LP when passed to `flow` and protocol shares when
+/// passed to an adapter.
+#[derive(Clone, Copy, Debug)]
+pub enum DepositOp {
+    /// Deposit exactly `assets`, receiving at least `min_shares`.
+    Deposit { assets: u64, min_shares: u64 },
+    /// Receive `shares`, spending at most `max_assets`. Adapters may return
+    /// more protocol shares than asked; `flow` mints exactly the LP asked for.
+    Mint { shares: u64, max_assets: u64 },
+}
+
+/// Amounts are internal LP when passed to `flow` and protocol shares when
+/// passed to an adapter.
+#[derive(Clone, Copy, Debug)]
+pub enum WithdrawOp {
+    /// Receive at least `assets`, redeeming at most `max_shares`.
+    Withdraw { assets: u64, max_shares: u64 },
+    /// Redeem exactly `shares`, receiving at least `min_assets`.
+    Redeem { shares: u64, min_assets: u64 },
+}
+
+/// A lending protocol vaults can route underlying into. Implementations only
+/// issue the protocol CPIs; staging, LP accounting and bound checks live in
+/// `flow` and are shared by every protocol.
+pub trait YieldAdapter<'info> {
+    /// Underlying value of `shares` protocol shares at the current rate.
+    fn shares_to_assets(&self, shares: u64) -> Result<u64>;
+
+    /// Moves underlying staged in `vault_token_ata` into the protocol,
+    /// crediting shares to `vault_shares_ata`. Unspent underlying is left
+    /// in `vault_token_ata`.
+    fn deposit(
+        &self,
+        accounts: &VaultAccounts<'_, 'info>,
+        op: DepositOp,
+        vault_seeds: &[&[u8]],
+    ) -> Result<()>;
+
+    /// Redeems shares from `vault_shares_ata`, paying underlying into
+    /// `vault_token_ata`.
+    fn withdraw(
+        &self,
+        accounts: &VaultAccounts<'_, 'info>,
+        op: WithdrawOp,
+        vault_seeds: &[&[u8]],
+    ) -> Result<()>;
+
+    /// Underlying value of everything the vault holds in the protocol.
+    fn total_assets(&self, accounts: &VaultAccounts<'_, 'info>) -> Result<u64> {
+        self.shares_to_assets(accounts.vault_shares_ata.amount)
+    }
+}
@@ -3,6 +3,33 @@ use anchor_lang::prelude::*;
 #[constant]
 pub const SEED: &str = "anchor";
 
-pub const MASTER_WALLET: Pubkey = pubkey!("6RdP9KmhSwuUHRJ3T72TsVi3t4F2Luf7m3BRjh1w3Sor");
+pub const KLEND_PROGRAM_ID: Pubkey = pubkey!("KLend2g3cP87fffoy8q1mQqGKjrxjC8boSyAYavgmjD");
+
+#[constant]
+pub const SHARE_PRICE_PRECISION: u64 = 1_000_000_000_000u64;
+
+/// Passed as `lp_amount` to the redeem instructions to exit the whole position.
+#[constant]
+pub const REDEEM_ALL: u64 = u64::MAX;
+
+/// Internal LP and protocol shares added to both sides of every LP
+/// conversion, as if the vault held a position nobody can redeem.
+#[constant]
+pub const VIRTUAL_OFFSET: u64 = 1u64;
+
+#[constant]
+pub const BPS_DENOMINATOR: u64 = 10_000u64;
+
+#[constant]
+pub const MAX_PERFORMANCE_FEE_BPS: u16 = 5_000u16;
+
+/// Management fees are annual rates.
+#[constant]
+pub const MAX_MANAGEMENT_FEE_BPS: u16 = 500u16;
+
+/// Most recipients claimed fees can be split between.
+pub const MAX_FEE_SPLITS: usize = 4;
+
+pub const SECONDS_PER_YEAR: u64 = 365 * 24 * 60 * 60;
 
 pub const ANCHOR_DISCRIMINATOR: usize = 8;
@@ -14,4 +14,36 @@ pub enum ErrorCode {
     WrongToken,
     #[msg("Math Overflow")]
     MathOverflow,
+    #[msg("Vault belongs to a different protocol")]
+    WrongProtocol,
+    #[msg("Market account does not match the vault")]
+    WrongMarket,
+    #[msg("You are not the pending admin")]
+    NotPendingAdmin,
+    #[msg("Signer does not hold the required role")]
+    MissingRole,
+    #[msg("Invalid role holder")]
+    InvalidRoleHolder,
+    #[msg("Deposits are paused")]
+    DepositsPaused,
+    #[msg("Withdrawals are paused")]
+    WithdrawalsPaused,
+    #[msg("Vault deposit cap exceeded")]
+    DepositCapExceeded,
+    #[msg("User deposit cap exceeded")]
+    UserDepositCapExceeded,
+    #[msg("Invalid LP token metadata")]
+    InvalidMetadata,
+    #[msg("Vault still holds funds")]
+    VaultNotEmpty,
+    #[msg("Slippage tolerance exceeded")]
+    SlippageExceeded,
+    #[msg("Operation would lower the backing of internal LP")]
+    BackingInvariantViolated,
+    #[msg("Fee exceeds the allowed maximum")]
+    FeeTooHigh,
+    #[msg("Fee recipient account is required when a fee is due")]
+    FeeRecipientMissing,
+    #[msg("Fee split must name one to four destinations with nonzero weights")]
+    InvalidFeeSplit,
 }
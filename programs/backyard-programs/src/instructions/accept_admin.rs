@@ -0,0 +1,30 @@
+use crate::{errors::ErrorCode, AdminAccepted, Config};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct AcceptAdmin<'info> {
+    pub pending_admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.pending_admin == Some(pending_admin.key()) @ ErrorCode::NotPendingAdmin,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let previous_admin = config.admin;
+
+    config.admin = ctx.accounts.pending_admin.key();
+    config.pending_admin = None;
+
+    emit_cpi!(AdminAccepted {
+        previous_admin,
+        admin: config.admin,
+    });
+    Ok(())
+}
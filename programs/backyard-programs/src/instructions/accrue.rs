@@ -0,0 +1,80 @@
+use crate::{
+    adapters::{flow, shares_to_assets},
+    errors::ErrorCode,
+    Vault,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct Accrue<'info> {
+    #[account(
+        mut,
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Jupiter lending or Kamino vault state, read according to the vault protocol
+    #[account(address = vault.market @ ErrorCode::WrongMarket)]
+    pub market: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+        address = vault.external_lp @ ErrorCode::WrongToken
+    )]
+    pub external_lp: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = external_lp,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program_2022,
+        address = vault.internal_lp @ ErrorCode::WrongToken,
+    )]
+    pub lp_token: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Internal LP account of the vault's fee recipient, required when a fee is due.
+    #[account(
+        mut,
+        token::mint = lp_token,
+        token::authority = vault.fee_recipient,
+        token::token_program = token_program_2022,
+    )]
+    pub fee_recipient_lp_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_2022: Interface<'info, TokenInterface>,
+}
+
+/// Crystallises fees owed at the current exchange rate. Permissionless, so
+/// fees do not depend on the vault seeing deposits or withdrawals.
+pub fn accrue(ctx: Context<Accrue>, _vault_id: Pubkey) -> Result<()> {
+    let accounts = &mut *ctx.accounts;
+    let market = accounts.market.to_account_info();
+    let total_assets = shares_to_assets(
+        accounts.vault.protocol,
+        &market,
+        accounts.vault_lp_ata.amount,
+    )?;
+
+    let fees = flow::accrue_fees(
+        &mut accounts.vault,
+        &mut accounts.lp_token,
+        accounts.fee_recipient_lp_ata.as_deref_mut(),
+        &accounts.token_program_2022,
+        total_assets,
+    )?;
+
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    Ok(())
+}
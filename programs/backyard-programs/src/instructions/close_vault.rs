@@ -0,0 +1,185 @@
+use crate::{errors::ErrorCode, Config, Treasury, Vault, VaultClosed, VaultEntry, VaultStatus};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::Token2022,
+    token_interface::{close_account, CloseAccount, Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct CloseVault<'info> {
+    #[account(mut)]
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::NotOwner,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault_entry", vault.index.to_le_bytes().as_ref()],
+        bump = vault_entry.bump,
+    )]
+    pub vault_entry: Box<Account<'info, VaultEntry>>,
+
+    #[account(
+        mint::token_program = token_program,
+        address = vault.token @ ErrorCode::WrongToken
+    )]
+    pub token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mint::token_program = token_program,
+        address = vault.external_lp @ ErrorCode::WrongToken
+    )]
+    pub external_lp: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program_2022,
+        address = vault.internal_lp @ ErrorCode::WrongToken,
+        constraint = lp_token.supply == 0 @ ErrorCode::VaultNotEmpty,
+    )]
+    pub lp_token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+        constraint = vault_token_ata.amount == 0 @ ErrorCode::VaultNotEmpty,
+    )]
+    pub vault_token_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = external_lp,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+        constraint = vault_lp_ata.amount == 0 @ ErrorCode::VaultNotEmpty,
+    )]
+    pub vault_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// The vault's treasury and its fee accounts, required once fees were
+    /// set and closed along with the vault.
+    #[account(
+        mut,
+        close = admin,
+        seeds = [b"treasury", vault_id.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Option<Box<Account<'info, Treasury>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = lp_token,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_2022,
+        constraint = treasury_lp_ata.amount == 0 @ ErrorCode::VaultNotEmpty,
+    )]
+    pub treasury_lp_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    #[account(
+        mut,
+        associated_token::mint = token,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+        constraint = treasury_token_ata.amount == 0 @ ErrorCode::VaultNotEmpty,
+    )]
+    pub treasury_token_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_2022: Program<'info, Token2022>,
+}
+
+pub fn close_vault(ctx: Context<CloseVault>, vault_id: Pubkey) -> Result<()> {
+    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
+
+    // A vault that had fees set pays them to its treasury, which goes with it.
+    let has_treasury = ctx.accounts.vault.fee_recipient != Pubkey::default();
+    require!(
+        ctx.accounts.treasury.is_some() == has_treasury,
+        ErrorCode::FeeRecipientMissing
+    );
+    if let Some(treasury) = &ctx.accounts.treasury {
+        let treasury_seeds: &[&[u8]] = &[b"treasury", vault_id.as_ref(), &[treasury.bump]];
+        let treasury_lp_ata = ctx
+            .accounts
+            .treasury_lp_ata
+            .as_ref()
+            .ok_or(ErrorCode::FeeRecipientMissing)?;
+        let treasury_token_ata = ctx
+            .accounts
+            .treasury_token_ata
+            .as_ref()
+            .ok_or(ErrorCode::FeeRecipientMissing)?;
+
+        for (token_program, account) in [
+            (
+                ctx.accounts.token_program_2022.to_account_info(),
+                treasury_lp_ata.to_account_info(),
+            ),
+            (
+                ctx.accounts.token_program.to_account_info(),
+                treasury_token_ata.to_account_info(),
+            ),
+        ] {
+            close_account(CpiContext::new_with_signer(
+                token_program,
+                CloseAccount {
+                    account,
+                    destination: ctx.accounts.admin.to_account_info(),
+                    authority: treasury.to_account_info(),
+                },
+                &[treasury_seeds],
+            ))?;
+        }
+    }
+
+    for (token_program, account) in [
+        (
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.vault_token_ata.to_account_info(),
+        ),
+        (
+            ctx.accounts.token_program.to_account_info(),
+            ctx.accounts.vault_lp_ata.to_account_info(),
+        ),
+        (
+            ctx.accounts.token_program_2022.to_account_info(),
+            ctx.accounts.lp_token.to_account_info(),
+        ),
+    ] {
+        close_account(CpiContext::new_with_signer(
+            token_program,
+            CloseAccount {
+                account,
+                destination: ctx.accounts.admin.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ))?;
+    }
+
+    ctx.accounts.vault_entry.status = VaultStatus::Closed;
+
+    emit_cpi!(VaultClosed {
+        vault: ctx.accounts.vault.key(),
+        vault_id,
+    });
+    Ok(())
+}
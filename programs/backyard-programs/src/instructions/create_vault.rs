@@ -1,46 +1,309 @@
-use crate::{errors::ErrorCode, Vault, MASTER_WALLET};
-use anchor_lang::prelude::*;
-use anchor_spl::token_interface::{Mint, TokenInterface};
+use crate::{
+    adapters::{jupiter::load_lending, kamino::VaultStateHeader},
+    errors::ErrorCode,
+    metadata::{metadata_len, PROTOCOL_KEY, VAULT_ID_KEY},
+    Config, Protocol, Vault, VaultCreated, VaultEntry, VaultStatus, SHARE_PRICE_PRECISION,
+};
+use anchor_lang::{
+    prelude::*,
+    system_program::{create_account, CreateAccount},
+};
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_2022::{spl_token_2022::extension::ExtensionType, Token2022},
+    token_interface::{
+        find_mint_account_size, initialize_mint2, metadata_pointer_initialize,
+        mint_close_authority_initialize, non_transferable_mint_initialize,
+        spl_token_metadata_interface::state::{Field, TokenMetadata},
+        token_metadata_initialize, token_metadata_update_field, InitializeMint2,
+        MetadataPointerInitialize, Mint, MintCloseAuthorityInitialize,
+        NonTransferableMintInitialize, TokenAccount, TokenInterface, TokenMetadataInitialize,
+        TokenMetadataUpdateField,
+    },
+};
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(vault_id: Pubkey)]
 pub struct CreateVault<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub admin: Signer<'info>,
+
     #[account(
         mut,
-        address = MASTER_WALLET @ ErrorCode::NotOwner
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::NotOwner,
     )]
-    pub master: Signer<'info>,
+    pub config: Box<Account<'info, Config>>,
 
     #[account(mint::token_program = token_program)]
     pub token: Box<InterfaceAccount<'info, Mint>>,
 
-    #[account(mint::token_program = token_program_2022)]
-    pub internal_lp: Box<InterfaceAccount<'info, Mint>>,
+    /// CHECK: created and initialised as a Token-2022 mint by this instruction
+    #[account(
+        mut,
+        seeds = [b"internal_lp", vault_id.as_ref()],
+        bump,
+    )]
+    pub internal_lp: UncheckedAccount<'info>,
 
     #[account(mint::token_program = token_program)]
     pub external_lp: Box<InterfaceAccount<'info, Mint>>,
 
+    /// CHECK: Jupiter lending or Kamino vault state, validated against the protocol
+    pub market: UncheckedAccount<'info>,
+
     #[account(
         init,
-        payer = master,
+        payer = payer,
         space = 8 + Vault::INIT_SPACE,
         seeds = [b"vault", vault_id.as_ref()],
         bump,
     )]
     pub vault: Box<Account<'info, Vault>>,
 
+    #[account(
+        init,
+        payer = payer,
+        space = 8 + VaultEntry::INIT_SPACE,
+        seeds = [b"vault_entry", config.vault_count.to_le_bytes().as_ref()],
+        bump,
+    )]
+    pub vault_entry: Box<Account<'info, VaultEntry>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = token,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_token_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = external_lp,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
     pub token_program: Interface<'info, TokenInterface>,
-    pub token_program_2022: Interface<'info, TokenInterface>,
+    pub token_program_2022: Program<'info, Token2022>,
     pub system_program: Program<'info, System>,
 }
 
-pub fn create_vault(ctx: Context<CreateVault>, vault_id: Pubkey) -> Result<()> {
+impl<'info> CreateVault<'info> {
+    fn validate_market(&self, protocol: Protocol) -> Result<()> {
+        let (token_mint, shares_mint) = match protocol {
+            Protocol::JupiterLend => {
+                let lending = load_lending(&self.market)?;
+                (lending.mint, lending.f_token_mint)
+            }
+            Protocol::KaminoVault => {
+                let state = VaultStateHeader::load(&self.market)?;
+                (state.token_mint, state.shares_mint)
+            }
+        };
+
+        require_keys_eq!(token_mint, self.token.key(), ErrorCode::WrongMarket);
+        require_keys_eq!(shares_mint, self.external_lp.key(), ErrorCode::WrongMarket);
+        Ok(())
+    }
+
+    /// Internal LP is non-transferable, minted by the vault at its share
+    /// price, closable by the vault once retired, and carries its own token
+    /// metadata.
+    fn init_internal_lp(
+        &self,
+        internal_lp_seeds: &[&[u8]],
+        vault_seeds: &[&[u8]],
+        metadata: &TokenMetadata,
+    ) -> Result<()> {
+        let token_program = self.token_program_2022.to_account_info();
+        let mint = self.internal_lp.to_account_info();
+        let vault = self.vault.key();
+
+        let space = find_mint_account_size(Some(&vec![
+            ExtensionType::NonTransferable,
+            ExtensionType::MintCloseAuthority,
+            ExtensionType::MetadataPointer,
+        ]))?;
+        let lamports = Rent::get()?.minimum_balance(space + metadata_len(metadata)?);
+
+        create_account(
+            CpiContext::new_with_signer(
+                self.system_program.to_account_info(),
+                CreateAccount {
+                    from: self.payer.to_account_info(),
+                    to: mint.clone(),
+                },
+                &[internal_lp_seeds],
+            ),
+            lamports,
+            space as u64,
+            token_program.key,
+        )?;
+
+        non_transferable_mint_initialize(CpiContext::new(
+            token_program.clone(),
+            NonTransferableMintInitialize {
+                token_program_id: token_program.clone(),
+                mint: mint.clone(),
+            },
+        ))?;
+
+        mint_close_authority_initialize(
+            CpiContext::new(
+                token_program.clone(),
+                MintCloseAuthorityInitialize {
+                    token_program_id: token_program.clone(),
+                    mint: mint.clone(),
+                },
+            ),
+            Some(&vault),
+        )?;
+
+        metadata_pointer_initialize(
+            CpiContext::new(
+                token_program.clone(),
+                MetadataPointerInitialize {
+                    token_program_id: token_program.clone(),
+                    mint: mint.clone(),
+                },
+            ),
+            Some(vault),
+            Some(mint.key()),
+        )?;
+
+        initialize_mint2(
+            CpiContext::new(
+                token_program.clone(),
+                InitializeMint2 { mint: mint.clone() },
+            ),
+            self.token.decimals,
+            &vault,
+            None,
+        )?;
+
+        token_metadata_initialize(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TokenMetadataInitialize {
+                    program_id: token_program.clone(),
+                    metadata: mint.clone(),
+                    update_authority: self.vault.to_account_info(),
+                    mint_authority: self.vault.to_account_info(),
+                    mint: mint.clone(),
+                },
+                &[vault_seeds],
+            ),
+            metadata.name.clone(),
+            metadata.symbol.clone(),
+            metadata.uri.clone(),
+        )?;
+
+        for (key, value) in &metadata.additional_metadata {
+            token_metadata_update_field(
+                CpiContext::new_with_signer(
+                    token_program.clone(),
+                    TokenMetadataUpdateField {
+                        program_id: token_program.clone(),
+                        metadata: mint.clone(),
+                        update_authority: self.vault.to_account_info(),
+                    },
+                    &[vault_seeds],
+                ),
+                Field::Key(key.clone()),
+                value.clone(),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+pub fn create_vault(
+    ctx: Context<CreateVault>,
+    vault_id: Pubkey,
+    protocol: Protocol,
+    name: String,
+    symbol: String,
+    uri: String,
+) -> Result<()> {
+    ctx.accounts.validate_market(protocol)?;
+
+    let internal_lp_seeds: &[&[u8]] =
+        &[b"internal_lp", vault_id.as_ref(), &[ctx.bumps.internal_lp]];
+    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[ctx.bumps.vault]];
+    let metadata = TokenMetadata {
+        mint: ctx.accounts.internal_lp.key(),
+        name,
+        symbol,
+        uri,
+        additional_metadata: vec![
+            (PROTOCOL_KEY.to_string(), protocol.label().to_string()),
+            (VAULT_ID_KEY.to_string(), vault_id.to_string()),
+        ],
+        ..Default::default()
+    };
+    ctx.accounts
+        .init_internal_lp(internal_lp_seeds, vault_seeds, &metadata)?;
+    let index = ctx.accounts.config.vault_count;
     let vault = &mut ctx.accounts.vault;
 
     vault.vault_id = vault_id;
+    vault.index = index;
+    vault.protocol = protocol;
+    vault.market = ctx.accounts.market.key();
     vault.token = ctx.accounts.token.key();
     vault.external_lp = ctx.accounts.external_lp.key();
     vault.internal_lp = ctx.accounts.internal_lp.key();
+    vault.deposits_paused = false;
+    vault.withdrawals_paused = false;
+    vault.deposit_cap = 0;
+    vault.user_deposit_cap = 0;
+    vault.total_assets = 0;
+    vault.performance_fee_bps = 0;
+    vault.management_fee_bps = 0;
+    vault.last_fee_accrual = Clock::get()?.unix_timestamp;
+    vault.entry_fee_bps = 0;
+    vault.exit_fee_bps = 0;
+    vault.fee_recipient = Pubkey::default();
+    vault.fee_token_account = Pubkey::default();
+    vault.high_water_mark = SHARE_PRICE_PRECISION;
     vault.bump = ctx.bumps.vault;
+
+    let vault_entry = &mut ctx.accounts.vault_entry;
+
+    vault_entry.index = index;
+    vault_entry.vault = vault.key();
+    vault_entry.vault_id = vault_id;
+    vault_entry.protocol = protocol;
+    vault_entry.token = vault.token;
+    vault_entry.status = VaultStatus::Active;
+    vault_entry.bump = ctx.bumps.vault_entry;
+
+    let config = &mut ctx.accounts.config;
+    config.vault_count = config
+        .vault_count
+        .checked_add(1)
+        .ok_or(ErrorCode::MathOverflow)?;
+
+    let vault = &ctx.accounts.vault;
+    emit_cpi!(VaultCreated {
+        vault: vault.key(),
+        vault_id,
+        index,
+        protocol,
+        market: vault.market,
+        token: vault.token,
+        internal_lp: vault.internal_lp,
+        external_lp: vault.external_lp,
+    });
     Ok(())
 }
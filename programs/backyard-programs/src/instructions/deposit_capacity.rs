@@ -0,0 +1,59 @@
+use crate::{adapters::shares_to_assets, errors::ErrorCode, DepositCapacity, Vault};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct DepositCapacityView<'info> {
+    #[account(
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    /// CHECK: Jupiter lending or Kamino vault state, read according to the vault protocol
+    #[account(address = vault.market @ ErrorCode::WrongMarket)]
+    pub market: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+        address = vault.external_lp @ ErrorCode::WrongToken
+    )]
+    pub external_lp: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = external_lp,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = vault.internal_lp @ ErrorCode::WrongToken)]
+    pub lp_token: Box<InterfaceAccount<'info, Mint>>,
+
+    /// Internal LP account of the user to report the per-wallet room for.
+    #[account(token::mint = vault.internal_lp)]
+    pub user_lp_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn deposit_capacity(
+    ctx: Context<DepositCapacityView>,
+    _vault_id: Pubkey,
+) -> Result<DepositCapacity> {
+    let vault = &ctx.accounts.vault;
+    let market = ctx.accounts.market.to_account_info();
+
+    let vault_assets = shares_to_assets(vault.protocol, &market, ctx.accounts.vault_lp_ata.amount)?;
+    let user_assets = match &ctx.accounts.user_lp_ata {
+        Some(user_lp_ata) => Vault::assets_for_lp(
+            user_lp_ata.amount,
+            vault_assets,
+            ctx.accounts.lp_token.supply,
+        )?,
+        None => 0,
+    };
+
+    Ok(vault.deposit_capacity(vault_assets, user_assets))
+}
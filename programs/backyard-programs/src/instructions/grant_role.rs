@@ -0,0 +1,31 @@
+use crate::{errors::ErrorCode, Config, Role, RoleGranted};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct GrantRole<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::NotOwner,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+pub fn grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+    require_keys_neq!(holder, Pubkey::default(), ErrorCode::InvalidRoleHolder);
+
+    let config = &mut ctx.accounts.config;
+    let previous_holder = config.role_holder(role);
+    config.set_role_holder(role, holder);
+
+    emit_cpi!(RoleGranted {
+        role,
+        holder,
+        previous_holder,
+    });
+    Ok(())
+}
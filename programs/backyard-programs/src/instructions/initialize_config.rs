@@ -0,0 +1,49 @@
+use crate::{errors::ErrorCode, program::BackyardPrograms, Config, ConfigInitialized};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct InitializeConfig<'info> {
+    #[account(mut)]
+    pub upgrade_authority: Signer<'info>,
+
+    #[account(
+        init,
+        payer = upgrade_authority,
+        space = 8 + Config::INIT_SPACE,
+        seeds = [b"config"],
+        bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        constraint = backyard_programs.programdata_address()? == Some(program_data.key()) @ ErrorCode::NotOwner
+    )]
+    pub backyard_programs: Program<'info, BackyardPrograms>,
+
+    #[account(
+        constraint = program_data.upgrade_authority_address == Some(upgrade_authority.key()) @ ErrorCode::NotOwner
+    )]
+    pub program_data: Box<Account<'info, ProgramData>>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn initialize_config(ctx: Context<InitializeConfig>, admin: Pubkey) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    config.admin = admin;
+    config.pending_admin = None;
+    config.guardian = Pubkey::default();
+    config.curator = Pubkey::default();
+    config.fee_manager = Pubkey::default();
+    config.deposits_paused = false;
+    config.withdrawals_paused = false;
+    config.max_entry_fee_bps = 0;
+    config.max_exit_fee_bps = 0;
+    config.vault_count = 0;
+    config.bump = ctx.bumps.config;
+
+    emit_cpi!(ConfigInitialized { admin });
+    Ok(())
+}
@@ -0,0 +1,198 @@
+use crate::{
+    adapters::{flow, jupiter::JupiterLendAdapter, VaultAccounts},
+    errors::ErrorCode,
+    lending::{
+        accounts::{Lending, LendingAdmin},
+        program::Lending as LendingProgram,
+    },
+    Config, Protocol, Role, Treasury, Vault,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct JupiterClaimFees<'info> {
+    pub fee_manager: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury", vault_id.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mint::token_program = token_program,
+        address = vault.token @ ErrorCode::WrongToken
+    )]
+    pub output_token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = output_token,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = output_token,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_output_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program_2022,
+        address = vault.internal_lp @ ErrorCode::WrongToken
+    )]
+    pub lp_token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = f_token_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        associated_token::mint = lp_token,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_2022,
+    )]
+    pub treasury_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+        constraint = vault.protocol == Protocol::JupiterLend @ ErrorCode::WrongProtocol,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.has_role(Role::FeeManager, &fee_manager.key()) @ ErrorCode::MissingRole,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(has_one = liquidity_program @ ErrorCode::WrongMarket)]
+    pub lending_admin: Box<Account<'info, LendingAdmin>>,
+    #[account(
+        mut,
+        address = vault.market @ ErrorCode::WrongMarket,
+        has_one = f_token_mint @ ErrorCode::WrongMarket,
+        has_one = rewards_rate_model @ ErrorCode::WrongMarket,
+        constraint = lending.token_reserves_liquidity == supply_token_reserves_liquidity.key() @ ErrorCode::WrongMarket,
+        constraint = lending.supply_position_on_liquidity == lending_supply_position_on_liquidity.key() @ ErrorCode::WrongMarket,
+    )]
+    pub lending: Box<Account<'info, Lending>>,
+    #[account(
+      mut,
+      address = vault.external_lp @ ErrorCode::WrongToken
+    )]
+    pub f_token_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: pinned by lending
+    #[account(mut)]
+    pub supply_token_reserves_liquidity: AccountInfo<'info>,
+    /// CHECK: pinned by lending
+    #[account(mut)]
+    pub lending_supply_position_on_liquidity: AccountInfo<'info>,
+    /// CHECK: verify by jupiter
+    pub rate_model: AccountInfo<'info>,
+    /// CHECK: verify by jupiter
+    #[account(mut)]
+    pub jupiter_vault: AccountInfo<'info>,
+    /// CHECK: verify by jupiter
+    #[account(mut)]
+    pub claim_account: AccountInfo<'info>,
+    /// CHECK: verify by jupiter
+    #[account(mut)]
+    pub liquidity: AccountInfo<'info>,
+    /// CHECK: pinned by lending_admin
+    #[account(mut)]
+    pub liquidity_program: AccountInfo<'info>,
+    /// CHECK: belongs to Jupiter Lend Rewards program (jup7TthsMgcR9Y3L277b8Eo9uboVSmu1utkuXHNUKar), pinned by lending
+    pub rewards_rate_model: AccountInfo<'info>,
+    pub lending_program: Program<'info, LendingProgram>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_2022: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> JupiterClaimFees<'info> {
+    pub(crate) fn adapter(&mut self) -> (VaultAccounts<'_, 'info>, JupiterLendAdapter<'info>) {
+        (
+            VaultAccounts {
+                signer: self.treasury.as_ref().as_ref(),
+                config: &self.config,
+                vault: &mut self.vault,
+                token: &self.output_token,
+                signer_token_ata: &self.treasury_token_ata,
+                vault_token_ata: &mut self.vault_output_ata,
+                vault_shares_ata: &mut self.vault_lp_ata,
+                lp_token: &mut self.lp_token,
+                signer_lp_ata: &mut self.treasury_lp_ata,
+                fee_recipient_lp_ata: None,
+                fee_token_account: None,
+                token_program: &self.token_program,
+                token_program_2022: &self.token_program_2022,
+            },
+            JupiterLendAdapter {
+                lending_program: self.lending_program.to_account_info(),
+                lending_admin: self.lending_admin.to_account_info(),
+                lending: self.lending.to_account_info(),
+                f_token_mint: self.f_token_mint.to_account_info(),
+                supply_token_reserves_liquidity: self.supply_token_reserves_liquidity.clone(),
+                lending_supply_position_on_liquidity: self
+                    .lending_supply_position_on_liquidity
+                    .clone(),
+                rate_model: self.rate_model.clone(),
+                jupiter_vault: self.jupiter_vault.clone(),
+                claim_account: Some(self.claim_account.clone()),
+                liquidity: self.liquidity.clone(),
+                liquidity_program: self.liquidity_program.clone(),
+                rewards_rate_model: self.rewards_rate_model.clone(),
+                associated_token_program: self.associated_token_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+            },
+        )
+    }
+}
+
+/// Redeems the fee LP held by the vault's treasury on Jupiter and pays the
+/// treasury's underlying out by its fee split. The split's destination
+/// accounts are passed as remaining accounts, in order.
+pub fn jupiter_claim_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, JupiterClaimFees<'info>>,
+    vault_id: Pubkey,
+) -> Result<()> {
+    let treasury = (*ctx.accounts.treasury).clone();
+    let treasury_seeds: &[&[u8]] = &[b"treasury", vault_id.as_ref(), &[treasury.bump]];
+
+    let (mut accounts, adapter) = ctx.accounts.adapter();
+    let (fees, event) = flow::claim_fees(
+        &mut accounts,
+        &adapter,
+        &treasury,
+        treasury_seeds,
+        ctx.remaining_accounts,
+    )?;
+
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(())
+}
@@ -1,20 +1,19 @@
 use crate::{
+    adapters::{flow, jupiter::JupiterLendAdapter, DepositOp, VaultAccounts},
     errors::ErrorCode,
     lending::{
         accounts::{Lending, LendingAdmin},
-        cpi::{accounts::Deposit, deposit},
         program::Lending as LendingProgram,
     },
-    Vault,
+    Config, DepositResult, Protocol, Vault,
 };
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{
-        mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
-    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(vault_id: Pubkey)]
 pub struct JupiterDeposit<'info> {
@@ -54,7 +53,8 @@ pub struct JupiterDeposit<'info> {
     #[account(
       mut,
       mint::token_program = token_program_2022,
-      address = vault.internal_lp @ ErrorCode::WrongToken
+      address = vault.internal_lp @ ErrorCode::WrongToken,
+      constraint = lp_token.mint_authority == COption::Some(vault.key()) @ ErrorCode::InvalidLpMintAuthority,
     )]
     pub lp_token: Box<InterfaceAccount<'info, Mint>>,
 
@@ -67,15 +67,45 @@ pub struct JupiterDeposit<'info> {
     )]
     pub signer_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Internal LP account of the vault's fee recipient, required when a fee is due.
+    #[account(
+      mut,
+      token::mint = lp_token,
+      token::authority = vault.fee_recipient,
+      token::token_program = token_program_2022,
+    )]
+    pub fee_recipient_lp_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Underlying account of the vault's fee recipient, required when a fee is due.
+    #[account(mut, address = vault.fee_token_account @ ErrorCode::WrongToken)]
+    pub fee_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     #[account(
         mut,
         seeds = [b"vault", vault_id.as_ref()],
         bump = vault.bump,
+        constraint = vault.protocol == Protocol::JupiterLend @ ErrorCode::WrongProtocol,
+        constraint = !vault.deposits_paused @ ErrorCode::DepositsPaused,
     )]
     pub vault: Box<Account<'info, Vault>>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.deposits_paused @ ErrorCode::DepositsPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(has_one = liquidity_program @ ErrorCode::WrongMarket)]
     pub lending_admin: Box<Account<'info, LendingAdmin>>,
-    #[account(mut)]
+    #[account(
+        mut,
+        address = vault.market @ ErrorCode::WrongMarket,
+        has_one = f_token_mint @ ErrorCode::WrongMarket,
+        has_one = rewards_rate_model @ ErrorCode::WrongMarket,
+        constraint = lending.token_reserves_liquidity == supply_token_reserves_liquidity.key() @ ErrorCode::WrongMarket,
+        constraint = lending.supply_position_on_liquidity == lending_supply_position_on_liquidity.key() @ ErrorCode::WrongMarket,
+    )]
     pub lending: Box<Account<'info, Lending>>,
     #[account(
         mut,
@@ -84,10 +114,10 @@ pub struct JupiterDeposit<'info> {
     )]
     pub f_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: verify by jupiter
+    /// CHECK: pinned by lending
     #[account(mut)]
     pub supply_token_reserves_liquidity: AccountInfo<'info>,
-    /// CHECK: verify by jupiter
+    /// CHECK: pinned by lending
     #[account(mut)]
     pub lending_supply_position_on_liquidity: AccountInfo<'info>,
     /// CHECK: verify by jupiter
@@ -98,10 +128,10 @@ pub struct JupiterDeposit<'info> {
     /// CHECK: verify by jupiter
     #[account(mut)]
     pub liquidity: AccountInfo<'info>,
-    /// CHECK: verify by jupiter
+    /// CHECK: pinned by lending_admin
     #[account(mut)]
     pub liquidity_program: AccountInfo<'info>,
-    /// CHECK: belongs to Jupiter Lend Rewards program (jup7TthsMgcR9Y3L277b8Eo9uboVSmu1utkuXHNUKar)
+    /// CHECK: belongs to Jupiter Lend Rewards program (jup7TthsMgcR9Y3L277b8Eo9uboVSmu1utkuXHNUKar), pinned by lending
     pub rewards_rate_model: AccountInfo<'info>,
     pub lending_program: Program<'info, LendingProgram>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -110,74 +140,90 @@ pub struct JupiterDeposit<'info> {
     pub system_program: Program<'info, System>,
 }
 
+impl<'info> JupiterDeposit<'info> {
+    pub(crate) fn adapter(&mut self) -> (VaultAccounts<'_, 'info>, JupiterLendAdapter<'info>) {
+        (
+            VaultAccounts {
+                signer: &self.signer,
+                config: &self.config,
+                vault: &mut self.vault,
+                token: &self.input_token,
+                signer_token_ata: &self.signer_input_ata,
+                vault_token_ata: &mut self.vault_input_ata,
+                vault_shares_ata: &mut self.vault_lp_ata,
+                lp_token: &mut self.lp_token,
+                signer_lp_ata: &mut self.signer_lp_ata,
+                fee_recipient_lp_ata: self.fee_recipient_lp_ata.as_deref_mut(),
+                fee_token_account: self.fee_token_account.as_deref(),
+                token_program: &self.token_program,
+                token_program_2022: &self.token_program_2022,
+            },
+            JupiterLendAdapter {
+                lending_program: self.lending_program.to_account_info(),
+                lending_admin: self.lending_admin.to_account_info(),
+                lending: self.lending.to_account_info(),
+                f_token_mint: self.f_token_mint.to_account_info(),
+                supply_token_reserves_liquidity: self.supply_token_reserves_liquidity.clone(),
+                lending_supply_position_on_liquidity: self
+                    .lending_supply_position_on_liquidity
+                    .clone(),
+                rate_model: self.rate_model.clone(),
+                jupiter_vault: self.jupiter_vault.clone(),
+                claim_account: None,
+                liquidity: self.liquidity.clone(),
+                liquidity_program: self.liquidity_program.clone(),
+                rewards_rate_model: self.rewards_rate_model.clone(),
+                associated_token_program: self.associated_token_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+            },
+        )
+    }
+}
+
 pub fn jupiter_deposit(
     ctx: Context<JupiterDeposit>,
-    vault_id: Pubkey,
+    _vault_id: Pubkey,
     input_amount: u64,
-) -> Result<()> {
-    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
-
-    require!(input_amount > 0, ErrorCode::InvalidAmount);
-
-    transfer_checked(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.signer_input_ata.to_account_info(),
-                mint: ctx.accounts.input_token.to_account_info(),
-                to: ctx.accounts.vault_input_ata.to_account_info(),
-                authority: ctx.accounts.signer.to_account_info(),
-            },
-        ),
-        input_amount,
-        ctx.accounts.input_token.decimals,
+    min_lp_out: u64,
+) -> Result<DepositResult> {
+    let (mut accounts, adapter) = ctx.accounts.adapter();
+    let (fees, event, result) = flow::deposit(
+        &mut accounts,
+        &adapter,
+        DepositOp::Deposit {
+            assets: input_amount,
+            min_shares: min_lp_out,
+        },
     )?;
 
-    let lp_amount = deposit(
-        CpiContext::new_with_signer(
-            ctx.accounts.lending_program.to_account_info(),
-            Deposit {
-                signer: ctx.accounts.vault.to_account_info(),
-                depositor_token_account: ctx.accounts.vault_input_ata.to_account_info(),
-                recipient_token_account: ctx.accounts.vault_lp_ata.to_account_info(),
-                mint: ctx.accounts.input_token.to_account_info(),
-                lending_admin: ctx.accounts.lending_admin.to_account_info(),
-                lending: ctx.accounts.lending.to_account_info(),
-                f_token_mint: ctx.accounts.f_token_mint.to_account_info(),
-                supply_token_reserves_liquidity: ctx
-                    .accounts
-                    .supply_token_reserves_liquidity
-                    .to_account_info(),
-                lending_supply_position_on_liquidity: ctx
-                    .accounts
-                    .lending_supply_position_on_liquidity
-                    .to_account_info(),
-                rate_model: ctx.accounts.rate_model.to_account_info(),
-                vault: ctx.accounts.jupiter_vault.to_account_info(),
-                liquidity: ctx.accounts.liquidity.to_account_info(),
-                liquidity_program: ctx.accounts.liquidity_program.to_account_info(),
-                rewards_rate_model: ctx.accounts.rewards_rate_model.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-            },
-            &[vault_seeds],
-        ),
-        input_amount,
-    )?;
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(result)
+}
 
-    mint_to(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program_2022.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.lp_token.to_account_info(),
-                to: ctx.accounts.signer_lp_ata.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
-            },
-            &[vault_seeds],
-        ),
-        lp_amount.get(),
+/// Mints exactly `lp_amount` internal LP, pulling at most `max_underlying_in`
+/// from the signer. Whatever Jupiter does not consume is sent straight back.
+pub fn jupiter_mint(
+    ctx: Context<JupiterDeposit>,
+    _vault_id: Pubkey,
+    lp_amount: u64,
+    max_underlying_in: u64,
+) -> Result<DepositResult> {
+    let (mut accounts, adapter) = ctx.accounts.adapter();
+    let (fees, event, result) = flow::deposit(
+        &mut accounts,
+        &adapter,
+        DepositOp::Mint {
+            shares: lp_amount,
+            max_assets: max_underlying_in,
+        },
     )?;
 
-    Ok(())
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(result)
 }
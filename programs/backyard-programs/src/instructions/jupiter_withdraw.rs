@@ -1,21 +1,19 @@
 use crate::{
+    adapters::{flow, jupiter::JupiterLendAdapter, VaultAccounts, WithdrawOp},
     errors::ErrorCode,
     lending::{
         accounts::{Lending, LendingAdmin},
-        cpi::{accounts::Withdraw, withdraw},
         program::Lending as LendingProgram,
     },
-    Vault,
+    Config, Protocol, Vault, WithdrawResult,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{
-        burn_checked, transfer_checked, BurnChecked, Mint, TokenAccount, TokenInterface,
-        TransferChecked,
-    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(vault_id: Pubkey)]
 pub struct JupiterWithdraw<'info> {
@@ -68,15 +66,45 @@ pub struct JupiterWithdraw<'info> {
     )]
     pub signer_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Internal LP account of the vault's fee recipient, required when a fee is due.
+    #[account(
+      mut,
+      token::mint = lp_token,
+      token::authority = vault.fee_recipient,
+      token::token_program = token_program_2022,
+    )]
+    pub fee_recipient_lp_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Underlying account of the vault's fee recipient, required when a fee is due.
+    #[account(mut, address = vault.fee_token_account @ ErrorCode::WrongToken)]
+    pub fee_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     #[account(
         mut,
         seeds = [b"vault", vault_id.as_ref()],
         bump = vault.bump,
+        constraint = vault.protocol == Protocol::JupiterLend @ ErrorCode::WrongProtocol,
+        constraint = !vault.withdrawals_paused @ ErrorCode::WithdrawalsPaused,
     )]
     pub vault: Box<Account<'info, Vault>>,
 
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.withdrawals_paused @ ErrorCode::WithdrawalsPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(has_one = liquidity_program @ ErrorCode::WrongMarket)]
     pub lending_admin: Box<Account<'info, LendingAdmin>>,
-    #[account(mut)]
+    #[account(
+        mut,
+        address = vault.market @ ErrorCode::WrongMarket,
+        has_one = f_token_mint @ ErrorCode::WrongMarket,
+        has_one = rewards_rate_model @ ErrorCode::WrongMarket,
+        constraint = lending.token_reserves_liquidity == supply_token_reserves_liquidity.key() @ ErrorCode::WrongMarket,
+        constraint = lending.supply_position_on_liquidity == lending_supply_position_on_liquidity.key() @ ErrorCode::WrongMarket,
+    )]
     pub lending: Box<Account<'info, Lending>>,
     #[account(
       mut,
@@ -84,10 +112,10 @@ pub struct JupiterWithdraw<'info> {
     )]
     pub f_token_mint: Box<InterfaceAccount<'info, Mint>>,
 
-    /// CHECK: verify by jupiter
+    /// CHECK: pinned by lending
     #[account(mut)]
     pub supply_token_reserves_liquidity: AccountInfo<'info>,
-    /// CHECK: verify by jupiter
+    /// CHECK: pinned by lending
     #[account(mut)]
     pub lending_supply_position_on_liquidity: AccountInfo<'info>,
     /// CHECK: verify by jupiter
@@ -101,10 +129,10 @@ pub struct JupiterWithdraw<'info> {
     /// CHECK: verify by jupiter
     #[account(mut)]
     pub liquidity: AccountInfo<'info>,
-    /// CHECK: verify by jupiter
+    /// CHECK: pinned by lending_admin
     #[account(mut)]
     pub liquidity_program: AccountInfo<'info>,
-    /// CHECK: belongs to Jupiter Lend Rewards program (jup7TthsMgcR9Y3L277b8Eo9uboVSmu1utkuXHNUKar)
+    /// CHECK: belongs to Jupiter Lend Rewards program (jup7TthsMgcR9Y3L277b8Eo9uboVSmu1utkuXHNUKar), pinned by lending
     pub rewards_rate_model: AccountInfo<'info>,
     pub lending_program: Program<'info, LendingProgram>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -113,76 +141,97 @@ pub struct JupiterWithdraw<'info> {
     pub system_program: Program<'info, System>,
 }
 
+impl<'info> JupiterWithdraw<'info> {
+    pub(crate) fn adapter(&mut self) -> (VaultAccounts<'_, 'info>, JupiterLendAdapter<'info>) {
+        (
+            VaultAccounts {
+                signer: &self.signer,
+                config: &self.config,
+                vault: &mut self.vault,
+                token: &self.output_token,
+                signer_token_ata: &self.signer_output_ata,
+                vault_token_ata: &mut self.vault_output_ata,
+                vault_shares_ata: &mut self.vault_lp_ata,
+                lp_token: &mut self.lp_token,
+                signer_lp_ata: &mut self.signer_lp_ata,
+                fee_recipient_lp_ata: self.fee_recipient_lp_ata.as_deref_mut(),
+                fee_token_account: self.fee_token_account.as_deref(),
+                token_program: &self.token_program,
+                token_program_2022: &self.token_program_2022,
+            },
+            JupiterLendAdapter {
+                lending_program: self.lending_program.to_account_info(),
+                lending_admin: self.lending_admin.to_account_info(),
+                lending: self.lending.to_account_info(),
+                f_token_mint: self.f_token_mint.to_account_info(),
+                supply_token_reserves_liquidity: self.supply_token_reserves_liquidity.clone(),
+                lending_supply_position_on_liquidity: self
+                    .lending_supply_position_on_liquidity
+                    .clone(),
+                rate_model: self.rate_model.clone(),
+                jupiter_vault: self.jupiter_vault.clone(),
+                claim_account: Some(self.claim_account.clone()),
+                liquidity: self.liquidity.clone(),
+                liquidity_program: self.liquidity_program.clone(),
+                rewards_rate_model: self.rewards_rate_model.clone(),
+                associated_token_program: self.associated_token_program.to_account_info(),
+                system_program: self.system_program.to_account_info(),
+            },
+        )
+    }
+}
+
 pub fn jupiter_withdraw(
     ctx: Context<JupiterWithdraw>,
-    vault_id: Pubkey,
+    _vault_id: Pubkey,
     output_amount: u64,
-) -> Result<()> {
-    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
-
-    require!(output_amount > 0, ErrorCode::InvalidAmount);
-
-    let lp_burned = withdraw(
-        CpiContext::new_with_signer(
-            ctx.accounts.lending_program.to_account_info(),
-            Withdraw {
-                signer: ctx.accounts.vault.to_account_info(),
-                owner_token_account: ctx.accounts.vault_lp_ata.to_account_info(),
-                recipient_token_account: ctx.accounts.vault_output_ata.to_account_info(),
-                lending_admin: ctx.accounts.lending_admin.to_account_info(),
-                lending: ctx.accounts.lending.to_account_info(),
-                mint: ctx.accounts.output_token.to_account_info(),
-                f_token_mint: ctx.accounts.f_token_mint.to_account_info(),
-                supply_token_reserves_liquidity: ctx
-                    .accounts
-                    .supply_token_reserves_liquidity
-                    .to_account_info(),
-                lending_supply_position_on_liquidity: ctx
-                    .accounts
-                    .lending_supply_position_on_liquidity
-                    .to_account_info(),
-                rate_model: ctx.accounts.rate_model.to_account_info(),
-                vault: ctx.accounts.jupiter_vault.to_account_info(),
-                claim_account: ctx.accounts.claim_account.to_account_info(), //???
-                liquidity: ctx.accounts.liquidity.to_account_info(),
-                liquidity_program: ctx.accounts.liquidity_program.to_account_info(),
-                rewards_rate_model: ctx.accounts.rewards_rate_model.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-                associated_token_program: ctx.accounts.associated_token_program.to_account_info(),
-                system_program: ctx.accounts.system_program.to_account_info(),
-            },
-            &[vault_seeds],
-        ),
-        output_amount,
+    max_lp_burn: u64,
+) -> Result<WithdrawResult> {
+    let (mut accounts, adapter) = ctx.accounts.adapter();
+    let (fees, event, result) = flow::withdraw(
+        &mut accounts,
+        &adapter,
+        WithdrawOp::Withdraw {
+            assets: output_amount,
+            max_shares: max_lp_burn,
+        },
+        false,
+        &[],
     )?;
 
-    burn_checked(
-        CpiContext::new(
-            ctx.accounts.token_program_2022.to_account_info(),
-            BurnChecked {
-                mint: ctx.accounts.lp_token.to_account_info(),
-                from: ctx.accounts.signer_lp_ata.to_account_info(),
-                authority: ctx.accounts.signer.to_account_info(),
-            },
-        ),
-        lp_burned.get(),
-        ctx.accounts.lp_token.decimals,
-    )?;
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(result)
+}
 
-    transfer_checked(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.vault_output_ata.to_account_info(),
-                mint: ctx.accounts.output_token.to_account_info(),
-                to: ctx.accounts.signer_output_ata.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
-            },
-            &[vault_seeds],
-        ),
-        output_amount,
-        ctx.accounts.output_token.decimals,
+/// Burns exactly `lp_amount` internal LP and pays out whatever the matching
+/// fTokens redeem for, as long as that is at least `min_underlying_out`.
+/// `REDEEM_ALL` burns the signer's whole balance, after which `close_lp_account`
+/// can close their LP account and refund its rent.
+pub fn jupiter_redeem(
+    ctx: Context<JupiterWithdraw>,
+    _vault_id: Pubkey,
+    lp_amount: u64,
+    min_underlying_out: u64,
+    close_lp_account: bool,
+) -> Result<WithdrawResult> {
+    let (mut accounts, adapter) = ctx.accounts.adapter();
+    let (fees, event, result) = flow::withdraw(
+        &mut accounts,
+        &adapter,
+        WithdrawOp::Redeem {
+            shares: lp_amount,
+            min_assets: min_underlying_out,
+        },
+        close_lp_account,
+        &[],
     )?;
 
-    Ok(())
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(result)
 }
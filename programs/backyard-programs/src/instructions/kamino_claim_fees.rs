@@ -0,0 +1,216 @@
+use crate::{
+    adapters::{
+        flow,
+        kamino::{KaminoReserveAccounts, KaminoVaultAdapter},
+        VaultAccounts,
+    },
+    errors::ErrorCode,
+    kamino_vault_converted::program::KaminoVault,
+    Config, Protocol, Role, Treasury, Vault, KLEND_PROGRAM_ID,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct KaminoVaultClaimFees<'info> {
+    pub fee_manager: Signer<'info>,
+
+    #[account(
+        seeds = [b"treasury", vault_id.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program,
+        address = vault.token @ ErrorCode::WrongToken
+    )]
+    pub output_token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = output_token,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      associated_token::mint = output_token,
+      associated_token::authority = vault,
+      associated_token::token_program = token_program,
+    )]
+    pub vault_output_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      associated_token::mint = shares_mint,
+      associated_token::authority = vault,
+      associated_token::token_program = token_program,
+    )]
+    pub vault_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+      mut,
+      mint::token_program = token_program_2022,
+      address = vault.internal_lp @ ErrorCode::WrongToken
+    )]
+    pub lp_token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        associated_token::mint = lp_token,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_2022,
+    )]
+    pub treasury_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+        constraint = vault.protocol == Protocol::KaminoVault @ ErrorCode::WrongProtocol,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.has_role(Role::FeeManager, &fee_manager.key()) @ ErrorCode::MissingRole,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Kamino vault state bound to the vault at creation
+    #[account(
+        mut,
+        address = vault.market @ ErrorCode::WrongMarket
+    )]
+    pub vault_state: AccountInfo<'info>,
+
+    /// CHECK: must be in the Kamino vault allocation strategy
+    #[account(mut)]
+    pub reserve: AccountInfo<'info>,
+
+    #[account(mut)]
+    pub token_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Kamino base vault authority
+    pub base_vault_authority: AccountInfo<'info>,
+
+    /// CHECK: Kamino event authority
+    pub kamino_event_authority: AccountInfo<'info>,
+
+    #[account(
+      mut,
+      mint::token_program = token_program,
+      address = vault.external_lp @ ErrorCode::WrongToken
+    )]
+    pub shares_mint: Box<InterfaceAccount<'info, Mint>>,
+
+    /// CHECK: account constraints checked in account trait
+    #[account(address = sysvar::instructions::ID)]
+    pub instruction_sysvar_account: AccountInfo<'info>,
+    /// CHECK: Kamino lending market
+    pub lending_market: AccountInfo<'info>,
+    /// CHECK: Kamino lending market authority
+    pub lending_market_authority: AccountInfo<'info>,
+    /// CHECK: Kamino reserve liquidity supply
+    #[account(mut)]
+    pub reserve_liquidity_supply: Box<InterfaceAccount<'info, TokenAccount>>,
+    /// CHECK: Kamino reserve collateral mint
+    #[account(mut)]
+    pub reserve_collateral_mint: AccountInfo<'info>,
+    /// CHECK: Kamino ctoken vault
+    #[account(mut)]
+    pub ctoken_vault: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    /// CHECK: Kamino lend program
+    #[account(address = KLEND_PROGRAM_ID @ ErrorCode::WrongMarket)]
+    pub klend_program: AccountInfo<'info>,
+    pub kamino_vault: Program<'info, KaminoVault>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_2022: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+impl<'info> KaminoVaultClaimFees<'info> {
+    pub(crate) fn adapter(
+        &mut self,
+        remaining_accounts: Vec<AccountInfo<'info>>,
+    ) -> (VaultAccounts<'_, 'info>, KaminoVaultAdapter<'info>) {
+        (
+            VaultAccounts {
+                signer: self.treasury.as_ref().as_ref(),
+                config: &self.config,
+                vault: &mut self.vault,
+                token: &self.output_token,
+                signer_token_ata: &self.treasury_token_ata,
+                vault_token_ata: &mut self.vault_output_ata,
+                vault_shares_ata: &mut self.vault_lp_ata,
+                lp_token: &mut self.lp_token,
+                signer_lp_ata: &mut self.treasury_lp_ata,
+                fee_recipient_lp_ata: None,
+                fee_token_account: None,
+                token_program: &self.token_program,
+                token_program_2022: &self.token_program_2022,
+            },
+            KaminoVaultAdapter {
+                kamino_vault: self.kamino_vault.to_account_info(),
+                vault_state: self.vault_state.clone(),
+                token_vault: self.token_vault.to_account_info(),
+                base_vault_authority: self.base_vault_authority.clone(),
+                shares_mint: self.shares_mint.to_account_info(),
+                kamino_event_authority: self.kamino_event_authority.clone(),
+                klend_program: self.klend_program.clone(),
+                reserve: Some(KaminoReserveAccounts {
+                    reserve: self.reserve.clone(),
+                    ctoken_vault: self.ctoken_vault.to_account_info(),
+                    instruction_sysvar_account: self.instruction_sysvar_account.clone(),
+                    lending_market: self.lending_market.clone(),
+                    lending_market_authority: self.lending_market_authority.clone(),
+                    reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+                    reserve_collateral_mint: self.reserve_collateral_mint.clone(),
+                }),
+                remaining_accounts,
+            },
+        )
+    }
+}
+
+/// Redeems the fee LP held by the vault's treasury on Kamino and pays the
+/// treasury's underlying out by its fee split. The split's destination
+/// accounts lead the remaining accounts, in order, ahead of those forwarded
+/// to Kamino.
+pub fn kamino_vault_claim_fees<'info>(
+    ctx: Context<'_, '_, '_, 'info, KaminoVaultClaimFees<'info>>,
+    vault_id: Pubkey,
+) -> Result<()> {
+    let treasury = (*ctx.accounts.treasury).clone();
+    let treasury_seeds: &[&[u8]] = &[b"treasury", vault_id.as_ref(), &[treasury.bump]];
+    let split_count = treasury.splits.len().min(ctx.remaining_accounts.len());
+    let (destinations, kamino_accounts) = ctx.remaining_accounts.split_at(split_count);
+
+    let (mut accounts, adapter) = ctx.accounts.adapter(kamino_accounts.to_vec());
+    adapter.validate_market()?;
+    let (fees, event) = flow::claim_fees(
+        &mut accounts,
+        &adapter,
+        &treasury,
+        treasury_seeds,
+        destinations,
+    )?;
+
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(())
+}
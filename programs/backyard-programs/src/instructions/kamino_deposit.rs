@@ -1,19 +1,16 @@
 use crate::{
+    adapters::{flow, kamino::KaminoVaultAdapter, DepositOp, VaultAccounts},
     errors::ErrorCode,
-    kamino_vault_converted::{
-        cpi::{accounts::Deposit, deposit},
-        program::KaminoVault,
-    },
-    Vault,
+    kamino_vault_converted::program::KaminoVault,
+    Config, DepositResult, Protocol, Vault, KLEND_PROGRAM_ID,
 };
-use anchor_lang::prelude::*;
+use anchor_lang::{prelude::*, solana_program::program_option::COption};
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{
-        mint_to, transfer_checked, Mint, MintTo, TokenAccount, TokenInterface, TransferChecked,
-    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(vault_id: Pubkey)]
 pub struct KaminoVaultDeposit<'info> {
@@ -53,7 +50,8 @@ pub struct KaminoVaultDeposit<'info> {
     #[account(
       mut,
       mint::token_program = token_program_2022,
-      address = vault.internal_lp @ ErrorCode::WrongToken
+      address = vault.internal_lp @ ErrorCode::WrongToken,
+      constraint = lp_token.mint_authority == COption::Some(vault.key()) @ ErrorCode::InvalidLpMintAuthority,
     )]
     pub lp_token: Box<InterfaceAccount<'info, Mint>>,
 
@@ -66,15 +64,40 @@ pub struct KaminoVaultDeposit<'info> {
     )]
     pub signer_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Internal LP account of the vault's fee recipient, required when a fee is due.
+    #[account(
+      mut,
+      token::mint = lp_token,
+      token::authority = vault.fee_recipient,
+      token::token_program = token_program_2022,
+    )]
+    pub fee_recipient_lp_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Underlying account of the vault's fee recipient, required when a fee is due.
+    #[account(mut, address = vault.fee_token_account @ ErrorCode::WrongToken)]
+    pub fee_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     #[account(
         mut,
         seeds = [b"vault", vault_id.as_ref()],
         bump = vault.bump,
+        constraint = vault.protocol == Protocol::KaminoVault @ ErrorCode::WrongProtocol,
+        constraint = !vault.deposits_paused @ ErrorCode::DepositsPaused,
     )]
     pub vault: Box<Account<'info, Vault>>,
 
-    /// CHECK: Kamino vault state
-    #[account(mut)]
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.deposits_paused @ ErrorCode::DepositsPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Kamino vault state bound to the vault at creation
+    #[account(
+        mut,
+        address = vault.market @ ErrorCode::WrongMarket
+    )]
     pub vault_state: AccountInfo<'info>,
 
     #[account(mut)]
@@ -84,7 +107,7 @@ pub struct KaminoVaultDeposit<'info> {
     pub base_vault_authority: AccountInfo<'info>,
 
     /// CHECK: Kamino event authority
-    pub event_authority: AccountInfo<'info>,
+    pub kamino_event_authority: AccountInfo<'info>,
 
     #[account(
       mut,
@@ -94,6 +117,7 @@ pub struct KaminoVaultDeposit<'info> {
     pub shares_mint: Box<InterfaceAccount<'info, Mint>>,
 
     /// CHECK: Kamino lend program
+    #[account(address = KLEND_PROGRAM_ID @ ErrorCode::WrongMarket)]
     pub klend_program: AccountInfo<'info>,
     pub kamino_vault: Program<'info, KaminoVault>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -102,73 +126,89 @@ pub struct KaminoVaultDeposit<'info> {
     pub system_program: Program<'info, System>,
 }
 
+impl<'info> KaminoVaultDeposit<'info> {
+    pub(crate) fn adapter(
+        &mut self,
+        remaining_accounts: Vec<AccountInfo<'info>>,
+    ) -> (VaultAccounts<'_, 'info>, KaminoVaultAdapter<'info>) {
+        (
+            VaultAccounts {
+                signer: &self.signer,
+                config: &self.config,
+                vault: &mut self.vault,
+                token: &self.input_token,
+                signer_token_ata: &self.signer_input_ata,
+                vault_token_ata: &mut self.vault_input_ata,
+                vault_shares_ata: &mut self.vault_lp_ata,
+                lp_token: &mut self.lp_token,
+                signer_lp_ata: &mut self.signer_lp_ata,
+                fee_recipient_lp_ata: self.fee_recipient_lp_ata.as_deref_mut(),
+                fee_token_account: self.fee_token_account.as_deref(),
+                token_program: &self.token_program,
+                token_program_2022: &self.token_program_2022,
+            },
+            KaminoVaultAdapter {
+                kamino_vault: self.kamino_vault.to_account_info(),
+                vault_state: self.vault_state.clone(),
+                token_vault: self.token_vault.to_account_info(),
+                base_vault_authority: self.base_vault_authority.clone(),
+                shares_mint: self.shares_mint.to_account_info(),
+                kamino_event_authority: self.kamino_event_authority.clone(),
+                klend_program: self.klend_program.clone(),
+                reserve: None,
+                remaining_accounts,
+            },
+        )
+    }
+}
+
 pub fn kamino_vault_deposit<'info>(
     ctx: Context<'_, '_, '_, 'info, KaminoVaultDeposit<'info>>,
-    vault_id: Pubkey,
+    _vault_id: Pubkey,
     input_amount: u64,
-) -> Result<()> {
-    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
-    let amount_lp_before = ctx.accounts.vault_lp_ata.amount;
-    let remaining_accounts = ctx.remaining_accounts.to_vec();
-
-    require!(input_amount > 0, ErrorCode::InvalidAmount);
-
-    transfer_checked(
-        CpiContext::new(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.signer_input_ata.to_account_info(),
-                mint: ctx.accounts.input_token.to_account_info(),
-                to: ctx.accounts.vault_input_ata.to_account_info(),
-                authority: ctx.accounts.signer.to_account_info(),
-            },
-        ),
-        input_amount,
-        ctx.accounts.input_token.decimals,
+    min_lp_out: u64,
+) -> Result<DepositResult> {
+    let (mut accounts, adapter) = ctx.accounts.adapter(ctx.remaining_accounts.to_vec());
+    adapter.validate_market()?;
+    let (fees, event, result) = flow::deposit(
+        &mut accounts,
+        &adapter,
+        DepositOp::Deposit {
+            assets: input_amount,
+            min_shares: min_lp_out,
+        },
     )?;
 
-    deposit(
-        CpiContext::new_with_signer(
-            ctx.accounts.kamino_vault.to_account_info(),
-            Deposit {
-                user: ctx.accounts.vault.to_account_info(),
-                vault_state: ctx.accounts.vault_state.to_account_info(),
-                token_vault: ctx.accounts.token_vault.to_account_info(),
-                token_mint: ctx.accounts.input_token.to_account_info(),
-                base_vault_authority: ctx.accounts.base_vault_authority.to_account_info(),
-                shares_mint: ctx.accounts.shares_mint.to_account_info(),
-                user_token_ata: ctx.accounts.vault_input_ata.to_account_info(),
-                user_shares_ata: ctx.accounts.vault_lp_ata.to_account_info(),
-                klend_program: ctx.accounts.klend_program.to_account_info(),
-                token_program: ctx.accounts.token_program.to_account_info(),
-                shares_token_program: ctx.accounts.token_program.to_account_info(),
-                event_authority: ctx.accounts.event_authority.to_account_info(),
-                program: ctx.accounts.kamino_vault.to_account_info(),
-            },
-            &[vault_seeds],
-        )
-        .with_remaining_accounts(remaining_accounts),
-        input_amount,
-    )?;
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(result)
+}
 
-    ctx.accounts.vault_lp_ata.reload()?;
-    let amount_lp_after = ctx.accounts.vault_lp_ata.amount;
-    let amount_to_mint = amount_lp_after
-        .checked_sub(amount_lp_before)
-        .ok_or(ErrorCode::MathOverflow)?;
-
-    mint_to(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program_2022.to_account_info(),
-            MintTo {
-                mint: ctx.accounts.lp_token.to_account_info(),
-                to: ctx.accounts.signer_lp_ata.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
-            },
-            &[vault_seeds],
-        ),
-        amount_to_mint,
+/// Mints exactly `lp_amount` internal LP. The deposit is sized from the last
+/// recorded AUM and may not exceed `max_underlying_in`; the Kamino shares it
+/// returns must back `lp_amount`, and any beyond that stay in the vault.
+pub fn kamino_vault_mint<'info>(
+    ctx: Context<'_, '_, '_, 'info, KaminoVaultDeposit<'info>>,
+    _vault_id: Pubkey,
+    lp_amount: u64,
+    max_underlying_in: u64,
+) -> Result<DepositResult> {
+    let (mut accounts, adapter) = ctx.accounts.adapter(ctx.remaining_accounts.to_vec());
+    adapter.validate_market()?;
+    let (fees, event, result) = flow::deposit(
+        &mut accounts,
+        &adapter,
+        DepositOp::Mint {
+            shares: lp_amount,
+            max_assets: max_underlying_in,
+        },
     )?;
 
-    Ok(())
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(result)
 }
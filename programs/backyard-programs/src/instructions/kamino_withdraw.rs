@@ -1,23 +1,20 @@
 use crate::{
-    errors::ErrorCode,
-    kamino_vault_converted::{
-        cpi::{
-            accounts::{Withdraw, WithdrawFromAvailable, WithdrawFromReserveAccounts},
-            withdraw,
-        },
-        program::KaminoVault,
+    adapters::{
+        flow,
+        kamino::{KaminoReserveAccounts, KaminoVaultAdapter},
+        VaultAccounts, WithdrawOp,
     },
-    Vault,
+    errors::ErrorCode,
+    kamino_vault_converted::program::KaminoVault,
+    Config, Protocol, Vault, WithdrawResult, KLEND_PROGRAM_ID,
 };
 use anchor_lang::prelude::*;
 use anchor_spl::{
     associated_token::AssociatedToken,
-    token_interface::{
-        burn_checked, transfer_checked, BurnChecked, Mint, TokenAccount, TokenInterface,
-        TransferChecked,
-    },
+    token_interface::{Mint, TokenAccount, TokenInterface},
 };
 
+#[event_cpi]
 #[derive(Accounts)]
 #[instruction(vault_id: Pubkey)]
 pub struct KaminoVaultWithdraw<'info> {
@@ -71,18 +68,43 @@ pub struct KaminoVaultWithdraw<'info> {
     )]
     pub signer_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
 
+    /// Internal LP account of the vault's fee recipient, required when a fee is due.
+    #[account(
+      mut,
+      token::mint = lp_token,
+      token::authority = vault.fee_recipient,
+      token::token_program = token_program_2022,
+    )]
+    pub fee_recipient_lp_ata: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
+    /// Underlying account of the vault's fee recipient, required when a fee is due.
+    #[account(mut, address = vault.fee_token_account @ ErrorCode::WrongToken)]
+    pub fee_token_account: Option<Box<InterfaceAccount<'info, TokenAccount>>>,
+
     #[account(
         mut,
         seeds = [b"vault", vault_id.as_ref()],
         bump = vault.bump,
+        constraint = vault.protocol == Protocol::KaminoVault @ ErrorCode::WrongProtocol,
+        constraint = !vault.withdrawals_paused @ ErrorCode::WithdrawalsPaused,
     )]
     pub vault: Box<Account<'info, Vault>>,
 
-    /// CHECK: Kamino vault state
-    #[account(mut)]
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = !config.withdrawals_paused @ ErrorCode::WithdrawalsPaused,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    /// CHECK: Kamino vault state bound to the vault at creation
+    #[account(
+        mut,
+        address = vault.market @ ErrorCode::WrongMarket
+    )]
     pub vault_state: AccountInfo<'info>,
 
-    /// CHECK: check in logic if there is allocation for this reserve
+    /// CHECK: must be in the Kamino vault allocation strategy
     #[account(mut)]
     pub reserve: AccountInfo<'info>,
 
@@ -93,7 +115,7 @@ pub struct KaminoVaultWithdraw<'info> {
     pub base_vault_authority: AccountInfo<'info>,
 
     /// CHECK: Kamino event authority
-    pub event_authority: AccountInfo<'info>,
+    pub kamino_event_authority: AccountInfo<'info>,
 
     #[account(
       mut,
@@ -120,6 +142,7 @@ pub struct KaminoVaultWithdraw<'info> {
     pub ctoken_vault: Box<InterfaceAccount<'info, TokenAccount>>,
 
     /// CHECK: Kamino lend program
+    #[account(address = KLEND_PROGRAM_ID @ ErrorCode::WrongMarket)]
     pub klend_program: AccountInfo<'info>,
     pub kamino_vault: Program<'info, KaminoVault>,
     pub associated_token_program: Program<'info, AssociatedToken>,
@@ -128,98 +151,106 @@ pub struct KaminoVaultWithdraw<'info> {
     pub system_program: Program<'info, System>,
 }
 
-pub fn kamino_vault_withdraw<'info>(
-    ctx: Context<'_, '_, '_, 'info, KaminoVaultWithdraw<'info>>,
-    vault_id: Pubkey,
-    lp_amount: u64,
-) -> Result<()> {
-    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
-    let amount_output_before = ctx.accounts.vault_output_ata.amount;
-    let remaining_accounts = ctx.remaining_accounts.to_vec();
-
-    require!(lp_amount > 0, ErrorCode::InvalidAmount);
-
-    burn_checked(
-        CpiContext::new(
-            ctx.accounts.token_program_2022.to_account_info(),
-            BurnChecked {
-                mint: ctx.accounts.lp_token.to_account_info(),
-                from: ctx.accounts.signer_lp_ata.to_account_info(),
-                authority: ctx.accounts.signer.to_account_info(),
+impl<'info> KaminoVaultWithdraw<'info> {
+    pub(crate) fn adapter(
+        &mut self,
+        remaining_accounts: Vec<AccountInfo<'info>>,
+    ) -> (VaultAccounts<'_, 'info>, KaminoVaultAdapter<'info>) {
+        (
+            VaultAccounts {
+                signer: &self.signer,
+                config: &self.config,
+                vault: &mut self.vault,
+                token: &self.output_token,
+                signer_token_ata: &self.signer_output_ata,
+                vault_token_ata: &mut self.vault_output_ata,
+                vault_shares_ata: &mut self.vault_lp_ata,
+                lp_token: &mut self.lp_token,
+                signer_lp_ata: &mut self.signer_lp_ata,
+                fee_recipient_lp_ata: self.fee_recipient_lp_ata.as_deref_mut(),
+                fee_token_account: self.fee_token_account.as_deref(),
+                token_program: &self.token_program,
+                token_program_2022: &self.token_program_2022,
             },
-        ),
-        lp_amount,
-        ctx.accounts.lp_token.decimals,
-    )?;
-
-    withdraw(
-        CpiContext::new_with_signer(
-            ctx.accounts.kamino_vault.to_account_info(),
-            Withdraw {
-                withdraw_from_available: WithdrawFromAvailable {
-                    user: ctx.accounts.vault.to_account_info(),
-                    base_vault_authority: ctx.accounts.base_vault_authority.to_account_info(),
-                    event_authority: ctx.accounts.event_authority.to_account_info(),
-                    klend_program: ctx.accounts.klend_program.to_account_info(),
-                    program: ctx.accounts.kamino_vault.to_account_info(),
-                    shares_mint: ctx.accounts.shares_mint.to_account_info(),
-                    shares_token_program: ctx.accounts.token_program.to_account_info(),
-                    token_mint: ctx.accounts.output_token.to_account_info(),
-                    token_program: ctx.accounts.token_program.to_account_info(),
-                    token_vault: ctx.accounts.token_vault.to_account_info(),
-                    user_shares_ata: ctx.accounts.vault_lp_ata.to_account_info(),
-                    user_token_ata: ctx.accounts.vault_output_ata.to_account_info(),
-                    vault_state: ctx.accounts.vault_state.to_account_info(),
-                },
-                withdraw_from_reserve_accounts: WithdrawFromReserveAccounts {
-                    ctoken_vault: ctx.accounts.ctoken_vault.to_account_info(),
-                    instruction_sysvar_account: ctx
-                        .accounts
-                        .instruction_sysvar_account
-                        .to_account_info(),
-                    lending_market: ctx.accounts.lending_market.to_account_info(),
-                    lending_market_authority: ctx
-                        .accounts
-                        .lending_market_authority
-                        .to_account_info(),
-                    reserve: ctx.accounts.reserve.to_account_info(),
-                    reserve_collateral_mint: ctx.accounts.reserve_collateral_mint.to_account_info(),
-                    reserve_collateral_token_program: ctx.accounts.token_program.to_account_info(),
-                    reserve_liquidity_supply: ctx
-                        .accounts
-                        .reserve_liquidity_supply
-                        .to_account_info(),
-                    vault_state: ctx.accounts.vault_state.to_account_info(),
-                },
-                event_authority: ctx.accounts.event_authority.to_account_info(),
-                program: ctx.accounts.kamino_vault.to_account_info(),
+            KaminoVaultAdapter {
+                kamino_vault: self.kamino_vault.to_account_info(),
+                vault_state: self.vault_state.clone(),
+                token_vault: self.token_vault.to_account_info(),
+                base_vault_authority: self.base_vault_authority.clone(),
+                shares_mint: self.shares_mint.to_account_info(),
+                kamino_event_authority: self.kamino_event_authority.clone(),
+                klend_program: self.klend_program.clone(),
+                reserve: Some(KaminoReserveAccounts {
+                    reserve: self.reserve.clone(),
+                    ctoken_vault: self.ctoken_vault.to_account_info(),
+                    instruction_sysvar_account: self.instruction_sysvar_account.clone(),
+                    lending_market: self.lending_market.clone(),
+                    lending_market_authority: self.lending_market_authority.clone(),
+                    reserve_liquidity_supply: self.reserve_liquidity_supply.to_account_info(),
+                    reserve_collateral_mint: self.reserve_collateral_mint.clone(),
+                }),
+                remaining_accounts,
             },
-            &[vault_seeds],
         )
-        .with_remaining_accounts(remaining_accounts),
-        lp_amount,
+    }
+}
+
+/// Pays out at least `output_amount`, redeeming the Kamino shares that buys
+/// at the last recorded AUM and burning the matching internal LP, which must
+/// not exceed `max_lp_burn`.
+pub fn kamino_vault_withdraw<'info>(
+    ctx: Context<'_, '_, '_, 'info, KaminoVaultWithdraw<'info>>,
+    _vault_id: Pubkey,
+    output_amount: u64,
+    max_lp_burn: u64,
+) -> Result<WithdrawResult> {
+    let (mut accounts, adapter) = ctx.accounts.adapter(ctx.remaining_accounts.to_vec());
+    adapter.validate_market()?;
+    let (fees, event, result) = flow::withdraw(
+        &mut accounts,
+        &adapter,
+        WithdrawOp::Withdraw {
+            assets: output_amount,
+            max_shares: max_lp_burn,
+        },
+        false,
+        &[],
     )?;
 
-    ctx.accounts.vault_output_ata.reload()?;
-    let amount_output_after = ctx.accounts.vault_output_ata.amount;
-    let amount_to_transfer = amount_output_after
-        .checked_sub(amount_output_before)
-        .ok_or(ErrorCode::MathOverflow)?;
-
-    transfer_checked(
-        CpiContext::new_with_signer(
-            ctx.accounts.token_program.to_account_info(),
-            TransferChecked {
-                from: ctx.accounts.vault_output_ata.to_account_info(),
-                mint: ctx.accounts.output_token.to_account_info(),
-                to: ctx.accounts.signer_output_ata.to_account_info(),
-                authority: ctx.accounts.vault.to_account_info(),
-            },
-            &[vault_seeds],
-        ),
-        amount_to_transfer,
-        ctx.accounts.output_token.decimals,
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(result)
+}
+
+/// Burns exactly `lp_amount` internal LP against its pro-rata share of the
+/// vault's Kamino shares and pays out the proceeds, which must reach
+/// `min_underlying_out`.
+/// Takes `REDEEM_ALL` and `close_lp_account` like `jupiter_redeem`.
+pub fn kamino_vault_redeem<'info>(
+    ctx: Context<'_, '_, '_, 'info, KaminoVaultWithdraw<'info>>,
+    _vault_id: Pubkey,
+    lp_amount: u64,
+    min_underlying_out: u64,
+    close_lp_account: bool,
+) -> Result<WithdrawResult> {
+    let (mut accounts, adapter) = ctx.accounts.adapter(ctx.remaining_accounts.to_vec());
+    adapter.validate_market()?;
+    let (fees, event, result) = flow::withdraw(
+        &mut accounts,
+        &adapter,
+        WithdrawOp::Redeem {
+            shares: lp_amount,
+            min_assets: min_underlying_out,
+        },
+        close_lp_account,
+        &[],
     )?;
 
-    Ok(())
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+    emit_cpi!(event);
+    Ok(result)
 }
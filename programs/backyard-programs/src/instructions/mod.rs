@@ -1,6 +1,54 @@
+pub mod initialize_config;
+pub use initialize_config::*;
+
+pub mod propose_admin;
+pub use propose_admin::*;
+
+pub mod accept_admin;
+pub use accept_admin::*;
+
+pub mod grant_role;
+pub use grant_role::*;
+
+pub mod revoke_role;
+pub use revoke_role::*;
+
+pub mod set_global_pause;
+pub use set_global_pause::*;
+
+pub mod set_vault_pause;
+pub use set_vault_pause::*;
+
+pub mod set_deposit_caps;
+pub use set_deposit_caps::*;
+
+pub mod set_fee_bounds;
+pub use set_fee_bounds::*;
+
+pub mod set_fees;
+pub use set_fees::*;
+
+pub mod set_fee_split;
+pub use set_fee_split::*;
+
+pub mod accrue;
+pub use accrue::*;
+
+pub mod deposit_capacity;
+pub use deposit_capacity::*;
+
 pub mod create_vault;
 pub use create_vault::*;
 
+pub mod update_lp_metadata;
+pub use update_lp_metadata::*;
+
+pub mod close_vault;
+pub use close_vault::*;
+
+pub mod sweep;
+pub use sweep::*;
+
 pub mod jupiter_deposit;
 pub use jupiter_deposit::*;
 
@@ -12,3 +60,9 @@ pub use kamino_deposit::*;
 
 pub mod kamino_withdraw;
 pub use kamino_withdraw::*;
+
+pub mod jupiter_claim_fees;
+pub use jupiter_claim_fees::*;
+
+pub mod kamino_claim_fees;
+pub use kamino_claim_fees::*;
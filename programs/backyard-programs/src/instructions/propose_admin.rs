@@ -0,0 +1,26 @@
+use crate::{errors::ErrorCode, AdminProposed, Config};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct ProposeAdmin<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::NotOwner,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+    ctx.accounts.config.pending_admin = Some(new_admin);
+
+    emit_cpi!(AdminProposed {
+        admin: ctx.accounts.admin.key(),
+        pending_admin: new_admin,
+    });
+    Ok(())
+}
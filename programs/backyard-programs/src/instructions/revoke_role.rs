@@ -0,0 +1,25 @@
+use crate::{errors::ErrorCode, Config, Role, RoleRevoked};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct RevokeRole<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::NotOwner,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+pub fn revoke_role(ctx: Context<RevokeRole>, role: Role) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let holder = config.role_holder(role);
+    config.set_role_holder(role, Pubkey::default());
+
+    emit_cpi!(RoleRevoked { role, holder });
+    Ok(())
+}
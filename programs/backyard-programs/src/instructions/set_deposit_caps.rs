@@ -0,0 +1,42 @@
+use crate::{errors::ErrorCode, Config, DepositCapsUpdated, Role, Vault};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct SetDepositCaps<'info> {
+    pub curator: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.has_role(Role::Curator, &curator.key()) @ ErrorCode::MissingRole,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn set_deposit_caps(
+    ctx: Context<SetDepositCaps>,
+    _vault_id: Pubkey,
+    deposit_cap: u64,
+    user_deposit_cap: u64,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+
+    vault.deposit_cap = deposit_cap;
+    vault.user_deposit_cap = user_deposit_cap;
+
+    emit_cpi!(DepositCapsUpdated {
+        vault: vault.key(),
+        deposit_cap,
+        user_deposit_cap,
+    });
+    Ok(())
+}
@@ -0,0 +1,44 @@
+use crate::{errors::ErrorCode, Config, FeeBoundsUpdated, BPS_DENOMINATOR};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetFeeBounds<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::NotOwner,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+/// Caps the entry and exit fees the fee manager may set. Fees already set
+/// above a lowered cap are charged at the cap.
+pub fn set_fee_bounds(
+    ctx: Context<SetFeeBounds>,
+    max_entry_fee_bps: u16,
+    max_exit_fee_bps: u16,
+) -> Result<()> {
+    require!(
+        (max_entry_fee_bps as u64) < BPS_DENOMINATOR,
+        ErrorCode::FeeTooHigh
+    );
+    require!(
+        (max_exit_fee_bps as u64) < BPS_DENOMINATOR,
+        ErrorCode::FeeTooHigh
+    );
+
+    let config = &mut ctx.accounts.config;
+
+    config.max_entry_fee_bps = max_entry_fee_bps;
+    config.max_exit_fee_bps = max_exit_fee_bps;
+
+    emit_cpi!(FeeBoundsUpdated {
+        max_entry_fee_bps,
+        max_exit_fee_bps,
+    });
+    Ok(())
+}
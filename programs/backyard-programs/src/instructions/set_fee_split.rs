@@ -0,0 +1,42 @@
+use crate::{errors::ErrorCode, Config, FeeSplit, FeeSplitUpdated, Role, Treasury};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct SetFeeSplit<'info> {
+    pub fee_manager: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.has_role(Role::FeeManager, &fee_manager.key()) @ ErrorCode::MissingRole,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"treasury", vault_id.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+}
+
+/// Sets the underlying token accounts claimed fees are paid to and their
+/// relative weights.
+pub fn set_fee_split(
+    ctx: Context<SetFeeSplit>,
+    _vault_id: Pubkey,
+    splits: Vec<FeeSplit>,
+) -> Result<()> {
+    Treasury::validate_splits(&splits)?;
+
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.splits = splits.clone();
+
+    emit_cpi!(FeeSplitUpdated {
+        vault: treasury.vault,
+        splits,
+    });
+    Ok(())
+}
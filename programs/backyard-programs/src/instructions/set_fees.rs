@@ -0,0 +1,171 @@
+use crate::{
+    adapters::{flow, shares_to_assets},
+    errors::ErrorCode,
+    Config, FeesUpdated, Role, Treasury, Vault, MAX_MANAGEMENT_FEE_BPS, MAX_PERFORMANCE_FEE_BPS,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    associated_token::AssociatedToken,
+    token_interface::{Mint, TokenAccount, TokenInterface},
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct SetFees<'info> {
+    #[account(mut)]
+    pub fee_manager: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        constraint = config.has_role(Role::FeeManager, &fee_manager.key()) @ ErrorCode::MissingRole,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        init_if_needed,
+        payer = fee_manager,
+        space = 8 + Treasury::INIT_SPACE,
+        seeds = [b"treasury", vault_id.as_ref()],
+        bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    /// CHECK: Jupiter lending or Kamino vault state, read according to the vault protocol
+    #[account(address = vault.market @ ErrorCode::WrongMarket)]
+    pub market: UncheckedAccount<'info>,
+
+    #[account(
+        mint::token_program = token_program,
+        address = vault.external_lp @ ErrorCode::WrongToken
+    )]
+    pub external_lp: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        associated_token::mint = external_lp,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program_2022,
+        address = vault.internal_lp @ ErrorCode::WrongToken,
+    )]
+    pub lp_token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = fee_manager,
+        associated_token::mint = lp_token,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program_2022,
+    )]
+    pub treasury_lp_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        mint::token_program = token_program,
+        address = vault.token @ ErrorCode::WrongToken,
+    )]
+    pub token: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        init_if_needed,
+        payer = fee_manager,
+        associated_token::mint = token,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_token_ata: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub token_program: Interface<'info, TokenInterface>,
+    pub token_program_2022: Interface<'info, TokenInterface>,
+    pub system_program: Program<'info, System>,
+}
+
+/// Sets the vault's fee rates, creating the treasury that collects them on
+/// first use. Fees owed so far are accrued at the old rates first.
+pub fn set_fees(
+    ctx: Context<SetFees>,
+    _vault_id: Pubkey,
+    performance_fee_bps: u16,
+    management_fee_bps: u16,
+    entry_fee_bps: u16,
+    exit_fee_bps: u16,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(
+        performance_fee_bps <= MAX_PERFORMANCE_FEE_BPS,
+        ErrorCode::FeeTooHigh
+    );
+    require!(
+        management_fee_bps <= MAX_MANAGEMENT_FEE_BPS,
+        ErrorCode::FeeTooHigh
+    );
+    require!(
+        entry_fee_bps <= config.max_entry_fee_bps,
+        ErrorCode::FeeTooHigh
+    );
+    require!(
+        exit_fee_bps <= config.max_exit_fee_bps,
+        ErrorCode::FeeTooHigh
+    );
+
+    let accounts = &mut *ctx.accounts;
+    let market = accounts.market.to_account_info();
+    let total_assets = shares_to_assets(
+        accounts.vault.protocol,
+        &market,
+        accounts.vault_lp_ata.amount,
+    )?;
+    // Fees only become due once a treasury is the recipient.
+    let fee_recipient_lp_ata = if accounts.vault.fee_recipient == accounts.treasury.key() {
+        Some(&mut *accounts.treasury_lp_ata)
+    } else {
+        None
+    };
+    let fees = flow::accrue_fees(
+        &mut accounts.vault,
+        &mut accounts.lp_token,
+        fee_recipient_lp_ata,
+        &accounts.token_program_2022,
+        total_assets,
+    )?;
+    if let Some(fees) = fees {
+        emit_cpi!(fees);
+    }
+
+    let vault = &mut ctx.accounts.vault;
+
+    vault.performance_fee_bps = performance_fee_bps;
+    vault.management_fee_bps = management_fee_bps;
+    vault.entry_fee_bps = entry_fee_bps;
+    vault.exit_fee_bps = exit_fee_bps;
+    vault.fee_recipient = ctx.accounts.treasury.key();
+    vault.fee_token_account = ctx.accounts.treasury_token_ata.key();
+
+    let treasury = &mut ctx.accounts.treasury;
+    treasury.vault = vault.key();
+    treasury.bump = ctx.bumps.treasury;
+
+    emit_cpi!(FeesUpdated {
+        vault: vault.key(),
+        performance_fee_bps,
+        management_fee_bps,
+        entry_fee_bps,
+        exit_fee_bps,
+        fee_recipient: vault.fee_recipient,
+        fee_token_account: vault.fee_token_account,
+    });
+    Ok(())
+}
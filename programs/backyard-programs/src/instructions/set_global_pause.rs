@@ -0,0 +1,41 @@
+use crate::{errors::ErrorCode, Config, GlobalPauseUpdated};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+pub struct SetGlobalPause<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        mut,
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+}
+
+pub fn set_global_pause(
+    ctx: Context<SetGlobalPause>,
+    deposits_paused: bool,
+    withdrawals_paused: bool,
+) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+    let authority = ctx.accounts.authority.key();
+    let unpausing = (config.deposits_paused && !deposits_paused)
+        || (config.withdrawals_paused && !withdrawals_paused);
+
+    require!(
+        config.can_set_pause(&authority, unpausing),
+        ErrorCode::MissingRole
+    );
+
+    config.deposits_paused = deposits_paused;
+    config.withdrawals_paused = withdrawals_paused;
+
+    emit_cpi!(GlobalPauseUpdated {
+        authority,
+        deposits_paused,
+        withdrawals_paused,
+    });
+    Ok(())
+}
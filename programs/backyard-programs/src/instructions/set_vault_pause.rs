@@ -0,0 +1,50 @@
+use crate::{errors::ErrorCode, Config, Vault, VaultPauseUpdated};
+use anchor_lang::prelude::*;
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct SetVaultPause<'info> {
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        mut,
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+}
+
+pub fn set_vault_pause(
+    ctx: Context<SetVaultPause>,
+    _vault_id: Pubkey,
+    deposits_paused: bool,
+    withdrawals_paused: bool,
+) -> Result<()> {
+    let vault = &mut ctx.accounts.vault;
+    let authority = ctx.accounts.authority.key();
+    let unpausing = (vault.deposits_paused && !deposits_paused)
+        || (vault.withdrawals_paused && !withdrawals_paused);
+
+    require!(
+        ctx.accounts.config.can_set_pause(&authority, unpausing),
+        ErrorCode::MissingRole
+    );
+
+    vault.deposits_paused = deposits_paused;
+    vault.withdrawals_paused = withdrawals_paused;
+
+    emit_cpi!(VaultPauseUpdated {
+        vault: vault.key(),
+        authority,
+        deposits_paused,
+        withdrawals_paused,
+    });
+    Ok(())
+}
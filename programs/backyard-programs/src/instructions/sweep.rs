@@ -0,0 +1,98 @@
+use crate::{errors::ErrorCode, Config, Swept, Treasury, Vault};
+use anchor_lang::prelude::*;
+use anchor_spl::token_interface::{
+    transfer_checked, Mint, TokenAccount, TokenInterface, TransferChecked,
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct Sweep<'info> {
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::NotOwner,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(mint::token_program = token_program)]
+    pub mint: Box<InterfaceAccount<'info, Mint>>,
+
+    #[account(
+        mut,
+        token::mint = mint,
+        token::authority = vault,
+        token::token_program = token_program,
+    )]
+    pub source: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(
+        seeds = [b"treasury", vault_id.as_ref()],
+        bump = treasury.bump,
+    )]
+    pub treasury: Box<Account<'info, Treasury>>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub destination: Box<InterfaceAccount<'info, TokenAccount>>,
+
+    #[account(address = vault.internal_lp @ ErrorCode::WrongToken)]
+    pub lp_token: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+/// Moves a vault-owned balance that backs no internal LP to the vault's
+/// treasury.
+/// External shares back every internal LP, so they can only be swept once
+/// the internal LP supply is zero.
+pub fn sweep(ctx: Context<Sweep>, vault_id: Pubkey) -> Result<()> {
+    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
+
+    let backing = if ctx.accounts.mint.key() == ctx.accounts.vault.external_lp {
+        if ctx.accounts.lp_token.supply > 0 {
+            ctx.accounts.source.amount
+        } else {
+            0
+        }
+    } else {
+        0
+    };
+    let amount = ctx.accounts.source.amount.saturating_sub(backing);
+    require!(amount > 0, ErrorCode::InvalidAmount);
+
+    transfer_checked(
+        CpiContext::new_with_signer(
+            ctx.accounts.token_program.to_account_info(),
+            TransferChecked {
+                from: ctx.accounts.source.to_account_info(),
+                mint: ctx.accounts.mint.to_account_info(),
+                to: ctx.accounts.destination.to_account_info(),
+                authority: ctx.accounts.vault.to_account_info(),
+            },
+            &[vault_seeds],
+        ),
+        amount,
+        ctx.accounts.mint.decimals,
+    )?;
+
+    emit_cpi!(Swept {
+        vault: ctx.accounts.vault.key(),
+        mint: ctx.accounts.mint.key(),
+        destination: ctx.accounts.destination.key(),
+        amount,
+    });
+    Ok(())
+}
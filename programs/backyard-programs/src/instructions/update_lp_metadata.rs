@@ -0,0 +1,105 @@
+use crate::{
+    errors::ErrorCode,
+    metadata::{fund_rent_exemption, metadata_len, read_token_metadata},
+    Config, LpMetadataUpdated, Vault,
+};
+use anchor_lang::prelude::*;
+use anchor_spl::{
+    token_2022::Token2022,
+    token_interface::{
+        spl_token_metadata_interface::state::Field, token_metadata_update_field, Mint,
+        TokenMetadataUpdateField,
+    },
+};
+
+#[event_cpi]
+#[derive(Accounts)]
+#[instruction(vault_id: Pubkey)]
+pub struct UpdateLpMetadata<'info> {
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub admin: Signer<'info>,
+
+    #[account(
+        seeds = [b"config"],
+        bump = config.bump,
+        has_one = admin @ ErrorCode::NotOwner,
+    )]
+    pub config: Box<Account<'info, Config>>,
+
+    #[account(
+        seeds = [b"vault", vault_id.as_ref()],
+        bump = vault.bump,
+    )]
+    pub vault: Box<Account<'info, Vault>>,
+
+    #[account(
+        mut,
+        mint::token_program = token_program_2022,
+        address = vault.internal_lp @ ErrorCode::WrongToken
+    )]
+    pub lp_token: Box<InterfaceAccount<'info, Mint>>,
+
+    pub token_program_2022: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+pub fn update_lp_metadata(
+    ctx: Context<UpdateLpMetadata>,
+    vault_id: Pubkey,
+    name: Option<String>,
+    symbol: Option<String>,
+    uri: Option<String>,
+) -> Result<()> {
+    let vault_seeds: &[&[u8]] = &[b"vault", vault_id.as_ref(), &[ctx.accounts.vault.bump]];
+    let token_program = ctx.accounts.token_program_2022.to_account_info();
+    let mint = ctx.accounts.lp_token.to_account_info();
+
+    let current = read_token_metadata(&mint)?;
+    let mut updated = current.clone();
+    let updates: Vec<(Field, String)> = [
+        (Field::Name, name),
+        (Field::Symbol, symbol),
+        (Field::Uri, uri),
+    ]
+    .into_iter()
+    .filter_map(|(field, value)| value.map(|value| (field, value)))
+    .collect();
+
+    for (field, value) in &updates {
+        updated.update(field.clone(), value.clone());
+    }
+
+    let new_len = mint.data_len() - metadata_len(&current)? + metadata_len(&updated)?;
+    fund_rent_exemption(
+        &ctx.accounts.payer.to_account_info(),
+        &mint,
+        &ctx.accounts.system_program.to_account_info(),
+        new_len,
+    )?;
+
+    for (field, value) in updates {
+        token_metadata_update_field(
+            CpiContext::new_with_signer(
+                token_program.clone(),
+                TokenMetadataUpdateField {
+                    program_id: token_program.clone(),
+                    metadata: mint.clone(),
+                    update_authority: ctx.accounts.vault.to_account_info(),
+                },
+                &[vault_seeds],
+            ),
+            field,
+            value,
+        )?;
+    }
+
+    emit_cpi!(LpMetadataUpdated {
+        vault: ctx.accounts.vault.key(),
+        name: updated.name,
+        symbol: updated.symbol,
+        uri: updated.uri,
+    });
+    Ok(())
+}
@@ -1,11 +1,14 @@
+pub mod adapters;
 pub mod constants;
 pub mod errors;
 pub mod instructions;
+pub mod metadata;
 pub mod state;
 
 use anchor_lang::prelude::*;
 
 pub use constants::*;
+#[allow(ambiguous_glob_reexports)]
 pub use instructions::*;
 pub use state::*;
 
@@ -18,39 +21,223 @@ declare_id!("CUuCLr2DXer9TKTgW6bqJRxQEu4JEvfGV6DcTsoE2E96");
 pub mod backyard_programs {
     use super::*;
 
-    pub fn create_vault(ctx: Context<CreateVault>, vault_id: Pubkey) -> Result<()> {
-        create_vault::create_vault(ctx, vault_id)
+    pub fn initialize_config(ctx: Context<InitializeConfig>, admin: Pubkey) -> Result<()> {
+        initialize_config::initialize_config(ctx, admin)
+    }
+
+    pub fn propose_admin(ctx: Context<ProposeAdmin>, new_admin: Pubkey) -> Result<()> {
+        propose_admin::propose_admin(ctx, new_admin)
+    }
+
+    pub fn accept_admin(ctx: Context<AcceptAdmin>) -> Result<()> {
+        accept_admin::accept_admin(ctx)
+    }
+
+    pub fn grant_role(ctx: Context<GrantRole>, role: Role, holder: Pubkey) -> Result<()> {
+        grant_role::grant_role(ctx, role, holder)
+    }
+
+    pub fn revoke_role(ctx: Context<RevokeRole>, role: Role) -> Result<()> {
+        revoke_role::revoke_role(ctx, role)
+    }
+
+    pub fn set_global_pause(
+        ctx: Context<SetGlobalPause>,
+        deposits_paused: bool,
+        withdrawals_paused: bool,
+    ) -> Result<()> {
+        set_global_pause::set_global_pause(ctx, deposits_paused, withdrawals_paused)
+    }
+
+    pub fn set_vault_pause(
+        ctx: Context<SetVaultPause>,
+        vault_id: Pubkey,
+        deposits_paused: bool,
+        withdrawals_paused: bool,
+    ) -> Result<()> {
+        set_vault_pause::set_vault_pause(ctx, vault_id, deposits_paused, withdrawals_paused)
+    }
+
+    pub fn set_deposit_caps(
+        ctx: Context<SetDepositCaps>,
+        vault_id: Pubkey,
+        deposit_cap: u64,
+        user_deposit_cap: u64,
+    ) -> Result<()> {
+        set_deposit_caps::set_deposit_caps(ctx, vault_id, deposit_cap, user_deposit_cap)
+    }
+
+    pub fn set_fee_bounds(
+        ctx: Context<SetFeeBounds>,
+        max_entry_fee_bps: u16,
+        max_exit_fee_bps: u16,
+    ) -> Result<()> {
+        set_fee_bounds::set_fee_bounds(ctx, max_entry_fee_bps, max_exit_fee_bps)
+    }
+
+    pub fn set_fees(
+        ctx: Context<SetFees>,
+        vault_id: Pubkey,
+        performance_fee_bps: u16,
+        management_fee_bps: u16,
+        entry_fee_bps: u16,
+        exit_fee_bps: u16,
+    ) -> Result<()> {
+        set_fees::set_fees(
+            ctx,
+            vault_id,
+            performance_fee_bps,
+            management_fee_bps,
+            entry_fee_bps,
+            exit_fee_bps,
+        )
+    }
+
+    pub fn set_fee_split(
+        ctx: Context<SetFeeSplit>,
+        vault_id: Pubkey,
+        splits: Vec<FeeSplit>,
+    ) -> Result<()> {
+        set_fee_split::set_fee_split(ctx, vault_id, splits)
+    }
+
+    pub fn accrue(ctx: Context<Accrue>, vault_id: Pubkey) -> Result<()> {
+        accrue::accrue(ctx, vault_id)
+    }
+
+    pub fn deposit_capacity(
+        ctx: Context<DepositCapacityView>,
+        vault_id: Pubkey,
+    ) -> Result<DepositCapacity> {
+        deposit_capacity::deposit_capacity(ctx, vault_id)
+    }
+
+    pub fn create_vault(
+        ctx: Context<CreateVault>,
+        vault_id: Pubkey,
+        protocol: Protocol,
+        name: String,
+        symbol: String,
+        uri: String,
+    ) -> Result<()> {
+        create_vault::create_vault(ctx, vault_id, protocol, name, symbol, uri)
+    }
+
+    pub fn update_lp_metadata(
+        ctx: Context<UpdateLpMetadata>,
+        vault_id: Pubkey,
+        name: Option<String>,
+        symbol: Option<String>,
+        uri: Option<String>,
+    ) -> Result<()> {
+        update_lp_metadata::update_lp_metadata(ctx, vault_id, name, symbol, uri)
+    }
+
+    pub fn close_vault(ctx: Context<CloseVault>, vault_id: Pubkey) -> Result<()> {
+        close_vault::close_vault(ctx, vault_id)
+    }
+
+    pub fn sweep(ctx: Context<Sweep>, vault_id: Pubkey) -> Result<()> {
+        sweep::sweep(ctx, vault_id)
     }
 
     pub fn jupiter_deposit(
         ctx: Context<JupiterDeposit>,
         vault_id: Pubkey,
         input_amount: u64,
-    ) -> Result<()> {
-        jupiter_deposit::jupiter_deposit(ctx, vault_id, input_amount)
+        min_lp_out: u64,
+    ) -> Result<DepositResult> {
+        jupiter_deposit::jupiter_deposit(ctx, vault_id, input_amount, min_lp_out)
+    }
+
+    pub fn jupiter_mint(
+        ctx: Context<JupiterDeposit>,
+        vault_id: Pubkey,
+        lp_amount: u64,
+        max_underlying_in: u64,
+    ) -> Result<DepositResult> {
+        jupiter_deposit::jupiter_mint(ctx, vault_id, lp_amount, max_underlying_in)
     }
 
     pub fn jupiter_withdraw(
         ctx: Context<JupiterWithdraw>,
         vault_id: Pubkey,
         output_amount: u64,
-    ) -> Result<()> {
-        jupiter_withdraw::jupiter_withdraw(ctx, vault_id, output_amount)
+        max_lp_burn: u64,
+    ) -> Result<WithdrawResult> {
+        jupiter_withdraw::jupiter_withdraw(ctx, vault_id, output_amount, max_lp_burn)
+    }
+
+    pub fn jupiter_redeem(
+        ctx: Context<JupiterWithdraw>,
+        vault_id: Pubkey,
+        lp_amount: u64,
+        min_underlying_out: u64,
+        close_lp_account: bool,
+    ) -> Result<WithdrawResult> {
+        jupiter_withdraw::jupiter_redeem(
+            ctx,
+            vault_id,
+            lp_amount,
+            min_underlying_out,
+            close_lp_account,
+        )
     }
 
     pub fn kamino_vault_deposit<'info>(
         ctx: Context<'_, '_, '_, 'info, KaminoVaultDeposit<'info>>,
         vault_id: Pubkey,
         input_amount: u64,
-    ) -> Result<()> {
-        kamino_deposit::kamino_vault_deposit(ctx, vault_id, input_amount)
+        min_lp_out: u64,
+    ) -> Result<DepositResult> {
+        kamino_deposit::kamino_vault_deposit(ctx, vault_id, input_amount, min_lp_out)
+    }
+
+    pub fn kamino_vault_mint<'info>(
+        ctx: Context<'_, '_, '_, 'info, KaminoVaultDeposit<'info>>,
+        vault_id: Pubkey,
+        lp_amount: u64,
+        max_underlying_in: u64,
+    ) -> Result<DepositResult> {
+        kamino_deposit::kamino_vault_mint(ctx, vault_id, lp_amount, max_underlying_in)
     }
 
     pub fn kamino_vault_withdraw<'info>(
+        ctx: Context<'_, '_, '_, 'info, KaminoVaultWithdraw<'info>>,
+        vault_id: Pubkey,
+        output_amount: u64,
+        max_lp_burn: u64,
+    ) -> Result<WithdrawResult> {
+        kamino_withdraw::kamino_vault_withdraw(ctx, vault_id, output_amount, max_lp_burn)
+    }
+
+    pub fn kamino_vault_redeem<'info>(
         ctx: Context<'_, '_, '_, 'info, KaminoVaultWithdraw<'info>>,
         vault_id: Pubkey,
         lp_amount: u64,
+        min_underlying_out: u64,
+        close_lp_account: bool,
+    ) -> Result<WithdrawResult> {
+        kamino_withdraw::kamino_vault_redeem(
+            ctx,
+            vault_id,
+            lp_amount,
+            min_underlying_out,
+            close_lp_account,
+        )
+    }
+
+    pub fn jupiter_claim_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, JupiterClaimFees<'info>>,
+        vault_id: Pubkey,
+    ) -> Result<()> {
+        jupiter_claim_fees::jupiter_claim_fees(ctx, vault_id)
+    }
+
+    pub fn kamino_vault_claim_fees<'info>(
+        ctx: Context<'_, '_, '_, 'info, KaminoVaultClaimFees<'info>>,
+        vault_id: Pubkey,
     ) -> Result<()> {
-        kamino_withdraw::kamino_vault_withdraw(ctx, vault_id, lp_amount)
+        kamino_claim_fees::kamino_vault_claim_fees(ctx, vault_id)
     }
 }
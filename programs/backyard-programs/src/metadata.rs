@@ -0,0 +1,56 @@
+use crate::errors::ErrorCode;
+use anchor_lang::{
+    prelude::*,
+    system_program::{transfer, Transfer},
+};
+use anchor_spl::{
+    token_2022::spl_token_2022::{
+        extension::{BaseStateWithExtensions, StateWithExtensions},
+        state::Mint,
+    },
+    token_interface::spl_token_metadata_interface::state::TokenMetadata,
+};
+
+/// Additional metadata keys tying an internal LP mint back to its vault.
+pub const PROTOCOL_KEY: &str = "protocol";
+pub const VAULT_ID_KEY: &str = "vault_id";
+
+pub fn read_token_metadata(mint: &AccountInfo) -> Result<TokenMetadata> {
+    let data = mint.try_borrow_data()?;
+    let mint = StateWithExtensions::<Mint>::unpack(&data)?;
+    mint.get_variable_len_extension::<TokenMetadata>()
+        .map_err(|_| ErrorCode::InvalidMetadata.into())
+}
+
+pub fn metadata_len(metadata: &TokenMetadata) -> Result<usize> {
+    metadata
+        .tlv_size_of()
+        .map_err(|_| ErrorCode::InvalidMetadata.into())
+}
+
+/// Token-2022 reallocs the mint when metadata grows but does not fund it,
+/// so top the account up to stay rent exempt at `len` bytes beforehand.
+pub fn fund_rent_exemption<'info>(
+    payer: &AccountInfo<'info>,
+    account: &AccountInfo<'info>,
+    system_program: &AccountInfo<'info>,
+    len: usize,
+) -> Result<()> {
+    let missing = Rent::get()?
+        .minimum_balance(len)
+        .saturating_sub(account.lamports());
+
+    if missing > 0 {
+        transfer(
+            CpiContext::new(
+                system_program.clone(),
+                Transfer {
+                    from: payer.clone(),
+                    to: account.clone(),
+                },
+            ),
+            missing,
+        )?;
+    }
+    Ok(())
+}
@@ -0,0 +1,58 @@
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum Role {
+    /// Can pause deposits and withdrawals.
+    Guardian,
+    /// Manages allocations and deposit caps.
+    Curator,
+    /// Sets and collects fees.
+    FeeManager,
+}
+
+#[account]
+#[derive(InitSpace)]
+pub struct Config {
+    pub admin: Pubkey,
+    pub pending_admin: Option<Pubkey>,
+    pub guardian: Pubkey,
+    pub curator: Pubkey,
+    pub fee_manager: Pubkey,
+    /// Global kill switches applied on top of each vault's own flags.
+    pub deposits_paused: bool,
+    pub withdrawals_paused: bool,
+    /// Highest entry and exit fees the fee manager may set on a vault.
+    pub max_entry_fee_bps: u16,
+    pub max_exit_fee_bps: u16,
+    /// Number of vaults ever created, and the index of the next registry entry.
+    pub vault_count: u64,
+    pub bump: u8,
+}
+
+impl Config {
+    pub fn role_holder(&self, role: Role) -> Pubkey {
+        match role {
+            Role::Guardian => self.guardian,
+            Role::Curator => self.curator,
+            Role::FeeManager => self.fee_manager,
+        }
+    }
+
+    pub fn set_role_holder(&mut self, role: Role, holder: Pubkey) {
+        match role {
+            Role::Guardian => self.guardian = holder,
+            Role::Curator => self.curator = holder,
+            Role::FeeManager => self.fee_manager = holder,
+        }
+    }
+
+    /// A revoked role is held by the default pubkey, which nobody can sign for.
+    pub fn has_role(&self, role: Role, key: &Pubkey) -> bool {
+        *key != Pubkey::default() && self.role_holder(role) == *key
+    }
+
+    /// The guardian may only pause; lifting a pause is reserved for the admin.
+    pub fn can_set_pause(&self, key: &Pubkey, unpausing: bool) -> bool {
+        self.admin == *key || (!unpausing && self.has_role(Role::Guardian, key))
+    }
+}
@@ -0,0 +1,149 @@
+use crate::{FeeSplit, Protocol, Role};
+use anchor_lang::prelude::*;
+
+#[event]
+pub struct ConfigInitialized {
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct AdminProposed {
+    pub admin: Pubkey,
+    pub pending_admin: Pubkey,
+}
+
+#[event]
+pub struct AdminAccepted {
+    pub previous_admin: Pubkey,
+    pub admin: Pubkey,
+}
+
+#[event]
+pub struct RoleGranted {
+    pub role: Role,
+    pub holder: Pubkey,
+    pub previous_holder: Pubkey,
+}
+
+#[event]
+pub struct RoleRevoked {
+    pub role: Role,
+    pub holder: Pubkey,
+}
+
+#[event]
+pub struct VaultPauseUpdated {
+    pub vault: Pubkey,
+    pub authority: Pubkey,
+    pub deposits_paused: bool,
+    pub withdrawals_paused: bool,
+}
+
+#[event]
+pub struct VaultCreated {
+    pub vault: Pubkey,
+    pub vault_id: Pubkey,
+    pub index: u64,
+    pub protocol: Protocol,
+    pub market: Pubkey,
+    pub token: Pubkey,
+    pub internal_lp: Pubkey,
+    pub external_lp: Pubkey,
+}
+
+#[event]
+pub struct Deposited {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub underlying_in: u64,
+    pub external_shares_received: u64,
+    pub internal_lp_minted: u64,
+    pub refunded: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct Withdrawn {
+    pub user: Pubkey,
+    pub vault: Pubkey,
+    pub lp_burned: u64,
+    pub external_shares_redeemed: u64,
+    pub underlying_out: u64,
+    pub fee: u64,
+}
+
+#[event]
+pub struct VaultClosed {
+    pub vault: Pubkey,
+    pub vault_id: Pubkey,
+}
+
+#[event]
+pub struct DepositCapsUpdated {
+    pub vault: Pubkey,
+    pub deposit_cap: u64,
+    pub user_deposit_cap: u64,
+}
+
+#[event]
+pub struct LpMetadataUpdated {
+    pub vault: Pubkey,
+    pub name: String,
+    pub symbol: String,
+    pub uri: String,
+}
+
+#[event]
+pub struct GlobalPauseUpdated {
+    pub authority: Pubkey,
+    pub deposits_paused: bool,
+    pub withdrawals_paused: bool,
+}
+
+#[event]
+pub struct FeesUpdated {
+    pub vault: Pubkey,
+    pub performance_fee_bps: u16,
+    pub management_fee_bps: u16,
+    pub entry_fee_bps: u16,
+    pub exit_fee_bps: u16,
+    pub fee_recipient: Pubkey,
+    pub fee_token_account: Pubkey,
+}
+
+#[event]
+pub struct FeeBoundsUpdated {
+    pub max_entry_fee_bps: u16,
+    pub max_exit_fee_bps: u16,
+}
+
+#[event]
+pub struct FeesAccrued {
+    pub vault: Pubkey,
+    pub fee_recipient: Pubkey,
+    pub management_fee_lp: u64,
+    pub performance_fee_lp: u64,
+    pub share_price: u64,
+    pub high_water_mark: u64,
+}
+
+#[event]
+pub struct FeeSplitUpdated {
+    pub vault: Pubkey,
+    pub splits: Vec<FeeSplit>,
+}
+
+#[event]
+pub struct FeesClaimed {
+    pub vault: Pubkey,
+    pub lp_redeemed: u64,
+    pub amount: u64,
+}
+
+#[event]
+pub struct Swept {
+    pub vault: Pubkey,
+    pub mint: Pubkey,
+    pub destination: Pubkey,
+    pub amount: u64,
+}
@@ -1,5 +1,17 @@
+pub mod config;
+pub use config::*;
+
 pub mod vault;
 pub use vault::*;
 
+pub mod registry;
+pub use registry::*;
+
+pub mod treasury;
+pub use treasury::*;
+
+pub mod results;
+pub use results::*;
+
 pub mod events;
 pub use events::*;
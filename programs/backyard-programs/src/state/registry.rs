@@ -0,0 +1,22 @@
+use crate::Protocol;
+use anchor_lang::prelude::*;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum VaultStatus {
+    Active,
+    Closed,
+}
+
+/// Registry slot for the vault created at `index`. Entries are addressed by
+/// index so clients can walk `0..config.vault_count` without scanning.
+#[account]
+#[derive(InitSpace)]
+pub struct VaultEntry {
+    pub index: u64,
+    pub vault: Pubkey,
+    pub vault_id: Pubkey,
+    pub protocol: Protocol,
+    pub token: Pubkey,
+    pub status: VaultStatus,
+    pub bump: u8,
+}
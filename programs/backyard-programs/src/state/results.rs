@@ -0,0 +1,18 @@
+use anchor_lang::prelude::*;
+
+/// Return data of the deposit instructions. `share_price` is the underlying
+/// value of one internal LP scaled by `SHARE_PRICE_PRECISION`, after the deposit.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct DepositResult {
+    pub assets_in: u64,
+    pub lp_minted: u64,
+    pub share_price: u64,
+}
+
+/// Return data of the withdraw instructions, priced like `DepositResult`.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct WithdrawResult {
+    pub lp_burned: u64,
+    pub assets_out: u64,
+    pub share_price: u64,
+}
@@ -0,0 +1,53 @@
+use crate::{errors::ErrorCode, MAX_FEE_SPLITS};
+use anchor_lang::prelude::*;
+
+/// Share of claimed fees paid to `destination`, an underlying token account.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub struct FeeSplit {
+    pub destination: Pubkey,
+    pub weight: u16,
+}
+
+/// Program-owned holder of a vault's fees: internal LP minted by fee accrual
+/// and underlying taken as entry and exit fees.
+#[account]
+#[derive(InitSpace)]
+pub struct Treasury {
+    pub vault: Pubkey,
+    #[max_len(MAX_FEE_SPLITS)]
+    pub splits: Vec<FeeSplit>,
+    pub bump: u8,
+}
+
+impl Treasury {
+    pub fn validate_splits(splits: &[FeeSplit]) -> Result<()> {
+        require!(
+            !splits.is_empty() && splits.len() <= MAX_FEE_SPLITS,
+            ErrorCode::InvalidFeeSplit
+        );
+        require!(
+            splits.iter().all(|split| split.weight > 0),
+            ErrorCode::InvalidFeeSplit
+        );
+        Ok(())
+    }
+
+    /// `amount` divided by weight, rounding down. The last split takes the
+    /// remainder so nothing is left behind.
+    pub fn split_amounts(&self, amount: u64) -> Result<Vec<u64>> {
+        Self::validate_splits(&self.splits)?;
+
+        let total_weight: u128 = self.splits.iter().map(|split| split.weight as u128).sum();
+        let mut remaining = amount;
+        let mut amounts: Vec<u64> = self
+            .splits
+            .iter()
+            .map(|split| (amount as u128 * split.weight as u128 / total_weight) as u64)
+            .collect();
+        for share in &amounts[..amounts.len() - 1] {
+            remaining -= share;
+        }
+        *amounts.last_mut().unwrap() = remaining;
+        Ok(amounts)
+    }
+}
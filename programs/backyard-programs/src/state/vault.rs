@@ -1,11 +1,665 @@
+use crate::{
+    errors::ErrorCode, Config, BPS_DENOMINATOR, SECONDS_PER_YEAR, SHARE_PRICE_PRECISION,
+    VIRTUAL_OFFSET,
+};
 use anchor_lang::prelude::*;
 
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, PartialEq, Eq, InitSpace, Debug)]
+pub enum Protocol {
+    JupiterLend,
+    KaminoVault,
+}
+
+impl Protocol {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Protocol::JupiterLend => "jupiter_lend",
+            Protocol::KaminoVault => "kamino_vault",
+        }
+    }
+}
+
 #[account]
 #[derive(InitSpace)]
 pub struct Vault {
     pub vault_id: Pubkey,
+    /// Position of the vault in the registry.
+    pub index: u64,
+    pub protocol: Protocol,
+    /// Jupiter `Lending` or Kamino `VaultState` the vault deposits into.
+    pub market: Pubkey,
     pub token: Pubkey,
     pub internal_lp: Pubkey,
     pub external_lp: Pubkey,
+    pub deposits_paused: bool,
+    pub withdrawals_paused: bool,
+    /// Maximum total deposits in underlying `token`, zero when uncapped.
+    pub deposit_cap: u64,
+    /// Maximum position per wallet in underlying `token`, zero when uncapped.
+    pub user_deposit_cap: u64,
+    /// Net asset value in underlying `token`, as of the last deposit or withdraw.
+    pub total_assets: u64,
+    /// Share of gains above `high_water_mark` paid to `fee_recipient`.
+    pub performance_fee_bps: u16,
+    /// Annual fee on the vault's net asset value, paid to `fee_recipient`.
+    pub management_fee_bps: u16,
+    /// Unix timestamp management fees have been charged up to.
+    pub last_fee_accrual: i64,
+    /// Share of each deposit and withdrawal paid to `fee_token_account`, up
+    /// to the `Config` bounds in force.
+    pub entry_fee_bps: u16,
+    pub exit_fee_bps: u16,
+    /// The vault's `Treasury`, owner of the internal LP account fees are
+    /// minted to.
+    pub fee_recipient: Pubkey,
+    /// Underlying `token` account entry and exit fees are paid to.
+    pub fee_token_account: Pubkey,
+    /// Highest share price performance fees have been charged up to, scaled
+    /// by `SHARE_PRICE_PRECISION`.
+    pub high_water_mark: u64,
     pub bump: u8,
 }
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Rounding {
+    Down,
+    Up,
+}
+
+fn mul_div(value: u64, numerator: u64, denominator: u64, rounding: Rounding) -> Result<u64> {
+    require!(denominator > 0, ErrorCode::MathOverflow);
+    let product = (value as u128)
+        .checked_mul(numerator as u128)
+        .ok_or(ErrorCode::MathOverflow)?;
+    let result = match rounding {
+        Rounding::Down => product / denominator as u128,
+        Rounding::Up => product.div_ceil(denominator as u128),
+    };
+    u64::try_from(result).map_err(|_| ErrorCode::MathOverflow.into())
+}
+
+/// Internal LP owed to the fee recipient and the high-water mark after it is
+/// minted.
+#[derive(Clone, Copy, Debug)]
+pub struct PerformanceFee {
+    pub fee_lp: u64,
+    pub high_water_mark: u64,
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug)]
+pub struct DepositCapacity {
+    pub vault_remaining: u64,
+    pub user_remaining: u64,
+}
+
+impl Vault {
+    fn remaining(cap: u64, used: u64) -> u64 {
+        if cap == 0 {
+            u64::MAX
+        } else {
+            cap.saturating_sub(used)
+        }
+    }
+
+    /// Room left under both caps, given the vault's and the user's current
+    /// positions valued in underlying `token`.
+    pub fn deposit_capacity(&self, vault_assets: u64, user_assets: u64) -> DepositCapacity {
+        DepositCapacity {
+            vault_remaining: Self::remaining(self.deposit_cap, vault_assets),
+            user_remaining: Self::remaining(self.user_deposit_cap, user_assets),
+        }
+    }
+
+    pub fn check_deposit_caps(
+        &self,
+        vault_assets: u64,
+        user_assets: u64,
+        amount: u64,
+    ) -> Result<()> {
+        let capacity = self.deposit_capacity(vault_assets, user_assets);
+
+        require!(
+            amount <= capacity.vault_remaining,
+            ErrorCode::DepositCapExceeded
+        );
+        require!(
+            amount <= capacity.user_remaining,
+            ErrorCode::UserDepositCapExceeded
+        );
+        Ok(())
+    }
+
+    // The vault's whole NAV is held as protocol shares, so internal LP is
+    // converted pro rata to those shares. That is the NAV share price at any
+    // common exchange rate, without depending on how fresh the rate is.
+    // `VIRTUAL_OFFSET` is added to both sides, so shares donated to the vault
+    // partly accrue to a position nobody owns. Inflating the price of a dust
+    // first deposit to round later deposits down then costs the donor more
+    // than it takes from depositors.
+
+    fn with_offset(amount: u64) -> Result<u64> {
+        amount
+            .checked_add(VIRTUAL_OFFSET)
+            .ok_or(ErrorCode::MathOverflow.into())
+    }
+
+    /// Internal LP worth `shares` out of the `shares_held` backing `lp_supply`.
+    pub fn lp_for_shares(
+        shares: u64,
+        shares_held: u64,
+        lp_supply: u64,
+        rounding: Rounding,
+    ) -> Result<u64> {
+        mul_div(
+            shares,
+            Self::with_offset(lp_supply)?,
+            Self::with_offset(shares_held)?,
+            rounding,
+        )
+    }
+
+    /// Protocol shares backing `lp` out of `lp_supply`.
+    pub fn shares_for_lp(
+        lp: u64,
+        shares_held: u64,
+        lp_supply: u64,
+        rounding: Rounding,
+    ) -> Result<u64> {
+        mul_div(
+            lp,
+            Self::with_offset(shares_held)?,
+            Self::with_offset(lp_supply)?,
+            rounding,
+        )
+    }
+
+    /// Underlying value of `lp` out of `lp_supply`, rounded down.
+    pub fn assets_for_lp(lp: u64, total_assets: u64, lp_supply: u64) -> Result<u64> {
+        if lp_supply == 0 {
+            return Ok(0);
+        }
+        mul_div(lp, total_assets, lp_supply, Rounding::Down)
+    }
+
+    /// Underlying value of one internal LP scaled by `SHARE_PRICE_PRECISION`.
+    /// An empty vault prices LP at one underlying unit.
+    pub fn share_price(&self, lp_supply: u64) -> Result<u64> {
+        if lp_supply == 0 {
+            return Ok(SHARE_PRICE_PRECISION);
+        }
+        mul_div(
+            self.total_assets,
+            SHARE_PRICE_PRECISION,
+            lp_supply,
+            Rounding::Down,
+        )
+    }
+
+    /// Deposits and withdrawals round in the vault's favour, so they must
+    /// never lower the protocol shares backing each internal LP, counting the
+    /// virtual position on both sides.
+    pub fn check_backing(
+        shares_before: u64,
+        lp_supply_before: u64,
+        shares_after: u64,
+        lp_supply_after: u64,
+    ) -> Result<()> {
+        if lp_supply_before == 0 {
+            return Ok(());
+        }
+        let offset = VIRTUAL_OFFSET as u128;
+        require!(
+            (shares_after as u128 + offset) * (lp_supply_before as u128 + offset)
+                >= (shares_before as u128 + offset) * (lp_supply_after as u128 + offset),
+            ErrorCode::BackingInvariantViolated
+        );
+        Ok(())
+    }
+
+    /// Performance fee owed on the gain of `total_assets` above the
+    /// high-water mark. The fee is minted as internal LP worth its share of
+    /// that gain, so holders keep at least the high-water mark price. The
+    /// mark only moves past a gain once its fee is minted, so gains too small
+    /// to charge yet are charged later. An empty vault restarts at the
+    /// first-deposit price.
+    pub fn performance_fee(&self, total_assets: u64, lp_supply: u64) -> Result<PerformanceFee> {
+        if lp_supply == 0 {
+            return Ok(PerformanceFee {
+                fee_lp: 0,
+                high_water_mark: SHARE_PRICE_PRECISION,
+            });
+        }
+
+        let principal = mul_div(
+            self.high_water_mark,
+            lp_supply,
+            SHARE_PRICE_PRECISION,
+            Rounding::Up,
+        )?;
+        let gain = total_assets.saturating_sub(principal);
+        let fee_assets = mul_div(
+            gain,
+            self.performance_fee_bps as u64,
+            BPS_DENOMINATOR,
+            Rounding::Down,
+        )?;
+        let fee_lp = if fee_assets == 0 {
+            0
+        } else {
+            mul_div(
+                fee_assets,
+                lp_supply,
+                total_assets - fee_assets,
+                Rounding::Down,
+            )?
+        };
+
+        let lp_supply_after = lp_supply
+            .checked_add(fee_lp)
+            .ok_or(ErrorCode::MathOverflow)?;
+        // Rounded up, so accruing again at the same NAV finds no gain.
+        let share_price = mul_div(
+            total_assets,
+            SHARE_PRICE_PRECISION,
+            lp_supply_after,
+            Rounding::Up,
+        )?;
+        let high_water_mark = if fee_lp == 0 && self.performance_fee_bps > 0 {
+            self.high_water_mark
+        } else {
+            share_price.max(self.high_water_mark)
+        };
+        Ok(PerformanceFee {
+            fee_lp,
+            high_water_mark,
+        })
+    }
+
+    /// Management fee owed on `lp_supply` since the last accrual, minted as
+    /// internal LP so holders are diluted pro rata. A single accrual never
+    /// takes more than half the vault, however long it sat idle.
+    pub fn management_fee(&self, lp_supply: u64, now: i64) -> Result<u64> {
+        let elapsed = now.saturating_sub(self.last_fee_accrual).max(0) as u128;
+        let denominator = BPS_DENOMINATOR as u128 * SECONDS_PER_YEAR as u128;
+        let rate = (self.management_fee_bps as u128)
+            .saturating_mul(elapsed)
+            .min(denominator / 2);
+        if rate == 0 {
+            return Ok(0);
+        }
+
+        let fee_lp = (lp_supply as u128) * rate / (denominator - rate);
+        u64::try_from(fee_lp).map_err(|_| ErrorCode::MathOverflow.into())
+    }
+
+    /// Moves the management fee accrual time to `now` once `management_fee_lp`
+    /// is charged on `lp_supply`. Time that earned a fee too small to mint
+    /// keeps accruing until it does.
+    pub fn advance_fee_accrual(&mut self, management_fee_lp: u64, lp_supply: u64, now: i64) {
+        if management_fee_lp > 0 || self.management_fee_bps == 0 || lp_supply == 0 {
+            self.last_fee_accrual = now;
+        }
+    }
+
+    /// Entry fee rate charged, capped by the admin's current bound in case it
+    /// was lowered after the rate was set.
+    fn entry_fee_rate(&self, config: &Config) -> u64 {
+        self.entry_fee_bps.min(config.max_entry_fee_bps) as u64
+    }
+
+    /// Exit fee rate charged, capped like `entry_fee_rate`.
+    fn exit_fee_rate(&self, config: &Config) -> u64 {
+        self.exit_fee_bps.min(config.max_exit_fee_bps) as u64
+    }
+
+    /// Entry fee charged on top of `assets_in`, so that it is the entry fee
+    /// rate of everything the depositor pays.
+    pub fn entry_fee(&self, config: &Config, assets_in: u64) -> Result<u64> {
+        let bps = self.entry_fee_rate(config);
+        mul_div(assets_in, bps, BPS_DENOMINATOR - bps, Rounding::Up)
+    }
+
+    /// Part of a deposit of `assets` left once the entry fee is paid.
+    pub fn net_of_entry_fee(&self, config: &Config, assets: u64) -> Result<u64> {
+        let fee = mul_div(
+            assets,
+            self.entry_fee_rate(config),
+            BPS_DENOMINATOR,
+            Rounding::Up,
+        )?;
+        Ok(assets - fee)
+    }
+
+    /// Exit fee withheld from `assets_out` redeemed from the protocol.
+    pub fn exit_fee(&self, config: &Config, assets_out: u64) -> Result<u64> {
+        mul_div(
+            assets_out,
+            self.exit_fee_rate(config),
+            BPS_DENOMINATOR,
+            Rounding::Up,
+        )
+    }
+
+    /// Underlying to redeem so that `assets` are left once the exit fee is
+    /// withheld.
+    pub fn gross_of_exit_fee(&self, config: &Config, assets: u64) -> Result<u64> {
+        let bps = self.exit_fee_rate(config);
+        mul_div(assets, BPS_DENOMINATOR, BPS_DENOMINATOR - bps, Rounding::Up)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(max_fee_bps: u16) -> Config {
+        Config {
+            admin: Pubkey::default(),
+            pending_admin: None,
+            guardian: Pubkey::default(),
+            curator: Pubkey::default(),
+            fee_manager: Pubkey::default(),
+            deposits_paused: false,
+            withdrawals_paused: false,
+            max_entry_fee_bps: max_fee_bps,
+            max_exit_fee_bps: max_fee_bps,
+            vault_count: 0,
+            bump: 0,
+        }
+    }
+
+    fn vault_with_fees(fee_bps: u16) -> Vault {
+        Vault {
+            entry_fee_bps: fee_bps,
+            exit_fee_bps: fee_bps,
+            ..vault()
+        }
+    }
+
+    fn vault() -> Vault {
+        Vault {
+            vault_id: Pubkey::default(),
+            index: 0,
+            protocol: Protocol::JupiterLend,
+            market: Pubkey::default(),
+            token: Pubkey::default(),
+            internal_lp: Pubkey::default(),
+            external_lp: Pubkey::default(),
+            deposits_paused: false,
+            withdrawals_paused: false,
+            deposit_cap: 0,
+            user_deposit_cap: 0,
+            total_assets: 0,
+            performance_fee_bps: 0,
+            management_fee_bps: 0,
+            last_fee_accrual: 0,
+            entry_fee_bps: 0,
+            exit_fee_bps: 0,
+            fee_recipient: Pubkey::default(),
+            fee_token_account: Pubkey::default(),
+            high_water_mark: SHARE_PRICE_PRECISION,
+            bump: 0,
+        }
+    }
+
+    #[test]
+    fn management_fee_is_the_annual_rate_of_the_diluted_supply() {
+        let vault = Vault {
+            management_fee_bps: 500,
+            ..vault()
+        };
+        let lp_supply = 1_000_000_000_000;
+
+        // A year at 5% mints 1/19 of the supply, 5% of it once minted.
+        let fee_lp = vault
+            .management_fee(lp_supply, SECONDS_PER_YEAR as i64)
+            .unwrap();
+        assert_eq!(fee_lp, 52_631_578_947);
+        assert_eq!(vault.management_fee(lp_supply, 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn management_fee_is_zero_without_a_rate_or_when_time_runs_backwards() {
+        let vault = Vault {
+            last_fee_accrual: 100,
+            ..vault()
+        };
+        assert_eq!(vault.management_fee(1_000_000, 1_000_000).unwrap(), 0);
+
+        let vault = Vault {
+            management_fee_bps: 500,
+            ..vault
+        };
+        assert_eq!(vault.management_fee(1_000_000, 50).unwrap(), 0);
+    }
+
+    #[test]
+    fn management_fee_never_takes_more_than_half_the_vault() {
+        let vault = Vault {
+            management_fee_bps: 500,
+            ..vault()
+        };
+        let lp_supply = 1_000_000;
+
+        // At 5%, the cap is reached after ten years.
+        let fee_lp = vault
+            .management_fee(lp_supply, 20 * SECONDS_PER_YEAR as i64)
+            .unwrap();
+        assert_eq!(fee_lp, lp_supply);
+    }
+
+    #[test]
+    fn management_fee_saturates_over_any_idle_time() {
+        let vault = Vault {
+            management_fee_bps: 500,
+            last_fee_accrual: i64::MIN,
+            ..vault()
+        };
+        assert_eq!(vault.management_fee(u64::MAX, i64::MAX).unwrap(), u64::MAX);
+    }
+
+    #[test]
+    fn fee_accrual_carries_forward_until_a_fee_is_minted() {
+        let mut vault = Vault {
+            management_fee_bps: 100,
+            ..vault()
+        };
+        let lp_supply = 1_000_000;
+
+        let fee_lp = vault.management_fee(lp_supply, 1).unwrap();
+        assert_eq!(fee_lp, 0);
+        vault.advance_fee_accrual(fee_lp, lp_supply, 1);
+        assert_eq!(vault.last_fee_accrual, 0);
+
+        // The fee is earned from the last mint, not from the last call.
+        assert_eq!(vault.management_fee(lp_supply, 3_153).unwrap(), 0);
+        let fee_lp = vault.management_fee(lp_supply, 3_154).unwrap();
+        assert_eq!(fee_lp, 1);
+        vault.advance_fee_accrual(fee_lp, lp_supply, 3_154);
+        assert_eq!(vault.last_fee_accrual, 3_154);
+    }
+
+    #[test]
+    fn fee_accrual_advances_when_nothing_can_be_charged() {
+        let mut vault = vault();
+        vault.advance_fee_accrual(0, 1_000_000, 10);
+        assert_eq!(vault.last_fee_accrual, 10);
+
+        vault.management_fee_bps = 100;
+        vault.advance_fee_accrual(0, 0, 20);
+        assert_eq!(vault.last_fee_accrual, 20);
+    }
+
+    #[test]
+    fn performance_fee_is_free_at_or_below_the_high_water_mark() {
+        let vault = Vault {
+            performance_fee_bps: 1_000,
+            ..vault()
+        };
+
+        for total_assets in [900_000, 1_000_000] {
+            let fee = vault.performance_fee(total_assets, 1_000_000).unwrap();
+            assert_eq!(fee.fee_lp, 0);
+            assert_eq!(fee.high_water_mark, SHARE_PRICE_PRECISION);
+        }
+    }
+
+    #[test]
+    fn performance_fee_mints_its_share_of_the_gain() {
+        let vault = Vault {
+            performance_fee_bps: 1_000,
+            ..vault()
+        };
+
+        // 10% of a 100_000 gain is 10_000 of the 1_100_000 assets, worth
+        // 9_174 LP once diluted into the supply.
+        let fee = vault.performance_fee(1_100_000, 1_000_000).unwrap();
+        assert_eq!(fee.fee_lp, 9_174);
+        assert_eq!(fee.high_water_mark, 1_090_000_336_910);
+
+        // Accruing again at the raised mark finds no gain.
+        let vault = Vault {
+            high_water_mark: fee.high_water_mark,
+            ..vault
+        };
+        let fee = vault.performance_fee(1_100_000, 1_009_174).unwrap();
+        assert_eq!(fee.fee_lp, 0);
+    }
+
+    #[test]
+    fn performance_fee_keeps_the_mark_until_a_fee_is_minted() {
+        let vault = Vault {
+            performance_fee_bps: 1_000,
+            ..vault()
+        };
+
+        // 10% of a gain of 5 rounds to nothing, so the gain is charged later.
+        let fee = vault.performance_fee(1_000_005, 1_000_000).unwrap();
+        assert_eq!(fee.fee_lp, 0);
+        assert_eq!(fee.high_water_mark, SHARE_PRICE_PRECISION);
+    }
+
+    #[test]
+    fn performance_fee_tracks_the_price_rounded_up_without_a_rate() {
+        let vault = vault();
+
+        let fee = vault.performance_fee(10, 3).unwrap();
+        assert_eq!(fee.fee_lp, 0);
+        assert_eq!(fee.high_water_mark, 3_333_333_333_334);
+
+        // The mark never falls.
+        let vault = Vault {
+            high_water_mark: fee.high_water_mark,
+            ..vault
+        };
+        let fee = vault.performance_fee(9, 3).unwrap();
+        assert_eq!(fee.high_water_mark, 3_333_333_333_334);
+    }
+
+    #[test]
+    fn performance_fee_restarts_an_empty_vault_at_the_first_deposit_price() {
+        let vault = Vault {
+            performance_fee_bps: 1_000,
+            high_water_mark: 3 * SHARE_PRICE_PRECISION,
+            ..vault()
+        };
+
+        let fee = vault.performance_fee(0, 0).unwrap();
+        assert_eq!(fee.fee_lp, 0);
+        assert_eq!(fee.high_water_mark, SHARE_PRICE_PRECISION);
+    }
+
+    #[test]
+    fn mul_div_rounds_in_the_direction_asked() {
+        assert_eq!(mul_div(10, 1, 3, Rounding::Down).unwrap(), 3);
+        assert_eq!(mul_div(10, 1, 3, Rounding::Up).unwrap(), 4);
+        assert_eq!(mul_div(9, 1, 3, Rounding::Up).unwrap(), 3);
+        assert_eq!(mul_div(0, 7, 3, Rounding::Up).unwrap(), 0);
+    }
+
+    #[test]
+    fn mul_div_overflows_only_on_the_result() {
+        assert_eq!(
+            mul_div(u64::MAX, u64::MAX, u64::MAX, Rounding::Up).unwrap(),
+            u64::MAX
+        );
+        assert!(mul_div(u64::MAX, 2, 1, Rounding::Down).is_err());
+        assert!(mul_div(1, 1, 0, Rounding::Down).is_err());
+    }
+
+    #[test]
+    fn fees_are_zero_at_zero_bps() {
+        let vault = vault_with_fees(0);
+        let config = config(100);
+
+        assert_eq!(vault.entry_fee(&config, 1_000_000).unwrap(), 0);
+        assert_eq!(
+            vault.net_of_entry_fee(&config, 1_000_000).unwrap(),
+            1_000_000
+        );
+        assert_eq!(vault.exit_fee(&config, 1_000_000).unwrap(), 0);
+        assert_eq!(
+            vault.gross_of_exit_fee(&config, 1_000_000).unwrap(),
+            1_000_000
+        );
+    }
+
+    #[test]
+    fn fees_round_up_in_the_vaults_favour() {
+        let vault = vault_with_fees(100);
+        let config = config(100);
+
+        // 1% of everything paid: 100 on top of 9_900.
+        assert_eq!(vault.entry_fee(&config, 9_900).unwrap(), 100);
+        assert_eq!(vault.entry_fee(&config, 1).unwrap(), 1);
+        assert_eq!(vault.net_of_entry_fee(&config, 10_000).unwrap(), 9_900);
+        assert_eq!(vault.net_of_entry_fee(&config, 1).unwrap(), 0);
+
+        assert_eq!(vault.exit_fee(&config, 10_000).unwrap(), 100);
+        assert_eq!(vault.exit_fee(&config, 1).unwrap(), 1);
+        assert_eq!(vault.gross_of_exit_fee(&config, 9_900).unwrap(), 10_000);
+        assert_eq!(vault.gross_of_exit_fee(&config, 1).unwrap(), 2);
+    }
+
+    #[test]
+    fn fees_are_capped_at_the_current_bounds() {
+        let vault = vault_with_fees(500);
+        let config = config(100);
+
+        assert_eq!(vault.entry_fee(&config, 9_900).unwrap(), 100);
+        assert_eq!(vault.exit_fee(&config, 10_000).unwrap(), 100);
+    }
+
+    #[test]
+    fn fees_hold_at_the_highest_bound() {
+        let vault = vault_with_fees(9_999);
+        let config = config(9_999);
+
+        assert_eq!(vault.entry_fee(&config, 1).unwrap(), 9_999);
+        assert_eq!(vault.net_of_entry_fee(&config, 10_000).unwrap(), 1);
+        assert_eq!(vault.gross_of_exit_fee(&config, 1).unwrap(), 10_000);
+        assert_eq!(vault.exit_fee(&config, 10_000).unwrap(), 9_999);
+        assert!(vault.gross_of_exit_fee(&config, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn fees_cover_the_amounts_they_are_sized_for() {
+        for bps in [0, 1, 30, 100, 2_500, 9_999] {
+            let vault = vault_with_fees(bps);
+            let config = config(bps);
+
+            for assets in (1..2_000).chain([999_999, 1_000_000, 123_456_789]) {
+                // Redeeming the gross amount leaves at least `assets` once
+                // the exit fee is withheld.
+                let gross = vault.gross_of_exit_fee(&config, assets).unwrap();
+                let exit_fee = vault.exit_fee(&config, gross).unwrap();
+                assert!(gross - exit_fee >= assets, "bps {bps}, assets {assets}");
+
+                // Staging the net amount leaves room for its entry fee.
+                let net = vault.net_of_entry_fee(&config, assets).unwrap();
+                let entry_fee = vault.entry_fee(&config, net).unwrap();
+                assert!(net + entry_fee <= assets, "bps {bps}, assets {assets}");
+            }
+        }
+    }
+}
@@ -5,22 +5,20 @@ import {
   Keypair,
   LAMPORTS_PER_SOL,
   PublicKey,
-  sendAndConfirmTransaction,
-  SystemProgram,
   SYSVAR_INSTRUCTIONS_PUBKEY,
-  Transaction,
+  BPF_LOADER_UPGRADEABLE_PROGRAM_ID,
   TransactionMessage,
   VersionedTransaction,
 } from "@solana/web3.js";
 import {
-  createInitializeMint2Instruction,
-  createInitializeNonTransferableMintInstruction,
-  ExtensionType,
+  createAssociatedTokenAccountIdempotent,
   getAssociatedTokenAddressSync,
-  getMintLen,
-  getOrCreateAssociatedTokenAccount,
+  getMint,
+  getNonTransferable,
+  getTokenMetadata,
   TOKEN_2022_PROGRAM_ID,
   TOKEN_PROGRAM_ID,
+  transferChecked,
 } from "@solana/spl-token";
 import { airdropIfRequired } from "@solana-developers/helpers";
 import { BackyardPrograms } from "../target/types/backyard_programs";
@@ -33,6 +31,7 @@ import {
 } from "@jup-ag/lend/earn";
 import { describe, it, expect, beforeAll } from 'vitest';
 import { getKaminoDepositContext, getKaminoWithdrawContext } from "./helpers/kamino-helpers";
+import { getCpiEvents, getReturnData } from "./helpers/event-helpers";
 
 dotenv.config();
 
@@ -49,8 +48,23 @@ describe("backyard-programs", () => {
 
   const secretUser = JSON.parse(process.env.USER_PRIVATE_KEY!);
   const user = Keypair.fromSecretKey(Uint8Array.from(secretUser));
+  const guardian = Keypair.generate();
+  const curator = Keypair.generate();
+  const feeManager = Keypair.generate();
   const usdc = new anchor.web3.PublicKey("EPjFWdd5AufqSSqeM2qN1xzybapC8G4wEGGkZwyTDt1v");
 
+  let configPda: PublicKey;
+
+  const vaultEntryPda = (index: anchor.BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("vault_entry"), index.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+  const treasuryPda = (vaultId: PublicKey) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("treasury"), vaultId.toBuffer()],
+      program.programId
+    )[0];
   let jupiterVaultPda: PublicKey;
   let internalLpJupiter: PublicKey;
 
@@ -65,6 +79,11 @@ describe("backyard-programs", () => {
       1 * LAMPORTS_PER_SOL
     );
 
+    configPda = PublicKey.findProgramAddressSync(
+      [Buffer.from("config")],
+      program.programId
+    )[0];
+
     jupiterVaultPda = PublicKey.findProgramAddressSync(
       [Buffer.from("vault"), jupiterVaultId.toBuffer()],
       program.programId
@@ -74,6 +93,123 @@ describe("backyard-programs", () => {
       [Buffer.from("vault"), kaminoVaultId.toBuffer()],
       program.programId
     )[0];
+
+    internalLpJupiter = PublicKey.findProgramAddressSync(
+      [Buffer.from("internal_lp"), jupiterVaultId.toBuffer()],
+      program.programId
+    )[0];
+
+    internalLpKamino = PublicKey.findProgramAddressSync(
+      [Buffer.from("internal_lp"), kaminoVaultId.toBuffer()],
+      program.programId
+    )[0];
+  });
+
+  it("initializes config with the protocol owner as admin", async () => {
+    const programData = PublicKey.findProgramAddressSync(
+      [program.programId.toBuffer()],
+      BPF_LOADER_UPGRADEABLE_PROGRAM_ID
+    )[0];
+
+    await program.methods
+      .initializeConfig(protocolOwner.publicKey)
+      .accounts({
+        upgradeAuthority: provider.wallet.publicKey,
+        programData,
+      })
+      .rpc();
+
+    const config = await program.account.config.fetch(configPda);
+
+    expect(config.admin.toBase58()).toEqual(protocolOwner.publicKey.toBase58());
+    expect(config.pendingAdmin).toBeNull();
+  });
+
+  it("transfers admin in two steps", async () => {
+    const newAdmin = Keypair.generate();
+
+    await program.methods
+      .proposeAdmin(newAdmin.publicKey)
+      .accounts({ admin: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
+
+    await expect(
+      program.methods
+        .acceptAdmin()
+        .accounts({ pendingAdmin: user.publicKey })
+        .signers([user])
+        .rpc()
+    ).rejects.toThrow();
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({ pendingAdmin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+
+    expect(
+      (await program.account.config.fetch(configPda)).admin.toBase58()
+    ).toEqual(newAdmin.publicKey.toBase58());
+
+    await program.methods
+      .proposeAdmin(protocolOwner.publicKey)
+      .accounts({ admin: newAdmin.publicKey })
+      .signers([newAdmin])
+      .rpc();
+
+    await program.methods
+      .acceptAdmin()
+      .accounts({ pendingAdmin: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
+
+    const config = await program.account.config.fetch(configPda);
+
+    expect(config.admin.toBase58()).toEqual(protocolOwner.publicKey.toBase58());
+    expect(config.pendingAdmin).toBeNull();
+  });
+
+  it("grants and revokes roles", async () => {
+    await expect(
+      program.methods
+        .grantRole({ guardian: {} }, user.publicKey)
+        .accounts({ admin: user.publicKey })
+        .signers([user])
+        .rpc()
+    ).rejects.toThrow();
+
+    for (const [role, holder] of [
+      [{ guardian: {} }, guardian],
+      [{ curator: {} }, curator],
+      [{ feeManager: {} }, feeManager],
+    ] as const) {
+      await program.methods
+        .grantRole(role, holder.publicKey)
+        .accounts({ admin: protocolOwner.publicKey })
+        .signers([protocolOwner])
+        .rpc();
+    }
+
+    let config = await program.account.config.fetch(configPda);
+    expect(config.guardian.toBase58()).toEqual(guardian.publicKey.toBase58());
+    expect(config.curator.toBase58()).toEqual(curator.publicKey.toBase58());
+    expect(config.feeManager.toBase58()).toEqual(feeManager.publicKey.toBase58());
+
+    await program.methods
+      .revokeRole({ curator: {} })
+      .accounts({ admin: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
+
+    config = await program.account.config.fetch(configPda);
+    expect(config.curator.toBase58()).toEqual(PublicKey.default.toBase58());
+
+    await program.methods
+      .grantRole({ curator: {} }, curator.publicKey)
+      .accounts({ admin: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
   });
 
   it("creates new lp token and vault PDA for Jupiter", async () => {
@@ -83,66 +219,133 @@ describe("backyard-programs", () => {
       connection,
     });
 
-    const lpTokenKeypair = Keypair.generate();
+    const tx = await program.methods
+      .createVault(
+        jupiterVaultId,
+        { jupiterLend: {} },
+        "Backyard Jupiter USDC",
+        "byjUSDC",
+        "https://backyard.finance/lp/jupiter-usdc.json"
+      )
+      .accounts({
+        payer: protocolOwner.publicKey,
+        admin: protocolOwner.publicKey,
+        token: usdc,
+        externalLp: depositContext.fTokenMint,
+        market: depositContext.lending,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([protocolOwner])
+      .rpc();
 
-    const extensions = [ExtensionType.NonTransferable];
-    const mintLen = getMintLen(extensions);
-    const lamports = await connection.getMinimumBalanceForRentExemption(
-      mintLen
-    );
+    expect(tx).not.toBeNull();
 
-    const createAccountIx = SystemProgram.createAccount({
-      fromPubkey: protocolOwner.publicKey,
-      newAccountPubkey: lpTokenKeypair.publicKey,
-      space: mintLen,
-      lamports,
-      programId: TOKEN_2022_PROGRAM_ID,
-    });
+    const vaultAccount = await program.account.vault.fetch(jupiterVaultPda);
 
-    const initializeNonTransferableIx =
-      createInitializeNonTransferableMintInstruction(
-        lpTokenKeypair.publicKey,
-        TOKEN_2022_PROGRAM_ID
-      );
+    expect(vaultAccount.vaultId.toBase58()).toEqual(jupiterVaultId.toBase58());
+    expect(vaultAccount.protocol).toEqual({ jupiterLend: {} });
+    expect(vaultAccount.market.toBase58()).toEqual(depositContext.lending.toBase58());
+    expect(vaultAccount.internalLp.toBase58()).toEqual(internalLpJupiter.toBase58());
 
-    const initializeMintIx = createInitializeMint2Instruction(
-      lpTokenKeypair.publicKey,
-      6,
-      jupiterVaultPda,
-      jupiterVaultPda,
+    const lpMint = await getMint(
+      connection,
+      internalLpJupiter,
+      undefined,
       TOKEN_2022_PROGRAM_ID
     );
+    expect(lpMint.mintAuthority?.toBase58()).toEqual(jupiterVaultPda.toBase58());
+    expect(getNonTransferable(lpMint)).not.toBeNull();
 
-    const setupTx = new Transaction().add(
-      createAccountIx,
-      initializeNonTransferableIx,
-      initializeMintIx
+    const vaultLpAta = getAssociatedTokenAddressSync(
+      depositContext.fTokenMint,
+      jupiterVaultPda,
+      true,
+      TOKEN_PROGRAM_ID
     );
+    expect(await connection.getAccountInfo(vaultLpAta)).not.toBeNull();
 
-    await sendAndConfirmTransaction(connection, setupTx, [
-      protocolOwner,
-      lpTokenKeypair,
+    const metadata = await getTokenMetadata(connection, internalLpJupiter);
+    expect(metadata?.symbol).toEqual("byjUSDC");
+    expect(metadata?.additionalMetadata).toEqual([
+      ["protocol", "jupiter_lend"],
+      ["vault_id", jupiterVaultId.toBase58()],
     ]);
+  });
 
-    internalLpJupiter = lpTokenKeypair.publicKey;
-
-    const tx = await program.methods
-      .createVault(jupiterVaultId)
+  it("updates internal LP metadata", async () => {
+    const uri = "https://backyard.finance/lp/jupiter-usdc-v2.json";
+
+    await expect(
+      program.methods
+        .updateLpMetadata(jupiterVaultId, null, null, uri)
+        .accounts({
+          payer: user.publicKey,
+          admin: user.publicKey,
+          lpToken: internalLpJupiter,
+        })
+        .signers([user])
+        .rpc()
+    ).rejects.toThrow();
+
+    await program.methods
+      .updateLpMetadata(jupiterVaultId, "Backyard Jupiter USD Coin", null, uri)
       .accounts({
-        token: usdc,
-        internalLp: internalLpJupiter,
-        externalLp: depositContext.fTokenMint,
-        tokenProgram: TOKEN_PROGRAM_ID,
-        tokenProgram2022: TOKEN_2022_PROGRAM_ID
+        payer: protocolOwner.publicKey,
+        admin: protocolOwner.publicKey,
+        lpToken: internalLpJupiter,
       })
       .signers([protocolOwner])
       .rpc();
 
-    expect(tx).not.toBeNull();
+    const metadata = await getTokenMetadata(connection, internalLpJupiter);
+    expect(metadata?.name).toEqual("Backyard Jupiter USD Coin");
+    expect(metadata?.symbol).toEqual("byjUSDC");
+    expect(metadata?.uri).toEqual(uri);
+  });
 
-    const vaultAccount = await program.account.vault.fetch(jupiterVaultPda);
+  it("lets the guardian pause but only the admin unpause", async () => {
+    await program.methods
+      .setVaultPause(jupiterVaultId, true, false)
+      .accounts({ authority: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
 
-    expect(vaultAccount.vaultId.toBase58()).toEqual(jupiterVaultId.toBase58());
+    let vaultAccount = await program.account.vault.fetch(jupiterVaultPda);
+    expect(vaultAccount.depositsPaused).toBe(true);
+    expect(vaultAccount.withdrawalsPaused).toBe(false);
+
+    await expect(
+      program.methods
+        .setVaultPause(jupiterVaultId, false, false)
+        .accounts({ authority: guardian.publicKey })
+        .signers([guardian])
+        .rpc()
+    ).rejects.toThrow();
+
+    await program.methods
+      .setVaultPause(jupiterVaultId, false, false)
+      .accounts({ authority: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
+
+    vaultAccount = await program.account.vault.fetch(jupiterVaultPda);
+    expect(vaultAccount.depositsPaused).toBe(false);
+
+    await program.methods
+      .setGlobalPause(true, true)
+      .accounts({ authority: guardian.publicKey })
+      .signers([guardian])
+      .rpc();
+
+    await program.methods
+      .setGlobalPause(false, false)
+      .accounts({ authority: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
+
+    const config = await program.account.config.fetch(configPda);
+    expect(config.depositsPaused).toBe(false);
+    expect(config.withdrawalsPaused).toBe(false);
   });
 
   it("deposit to Jupiter vault", async () => {
@@ -161,33 +364,12 @@ describe("backyard-programs", () => {
       connection,
     });
 
-    await getOrCreateAssociatedTokenAccount(
-      connection,
-      user,
-      usdc,
-      jupiterVaultPda,
-      true,
-      undefined,
-      undefined,
-      TOKEN_PROGRAM_ID
-    );
-    await getOrCreateAssociatedTokenAccount(
-      connection,
-      user,
-      depositContext.fTokenMint,
-      jupiterVaultPda,
-      true,
-      undefined,
-      undefined,
-      TOKEN_PROGRAM_ID
-    );
-
     const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
       units: 600_000,
     });
 
     const tx = await program.methods
-      .jupiterDeposit(jupiterVaultId, amount)
+      .jupiterDeposit(jupiterVaultId, amount, amount.divn(2))
       .accounts({
         signer: user.publicKey,
         inputToken: usdc,
@@ -207,7 +389,7 @@ describe("backyard-programs", () => {
       })
       .preInstructions([computeBudgetIx])
       .signers([user])
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     expect(tx).not.toBeNull();
 
@@ -229,7 +411,120 @@ describe("backyard-programs", () => {
 
     const userLpBalance = await connection.getTokenAccountBalance(userLpAccount);
     expect(Number(userLpBalance.value.amount)).toBeGreaterThan(0);
-    expect(vaultLpBalance.value.amount).toEqual(userLpBalance.value.amount);
+    // The first deposit into an empty vault mints one LP per underlying unit
+    // the external shares are worth, rounded down.
+    expect(BigInt(userLpBalance.value.amount) <= BigInt(amount.toString())).toBe(true);
+
+    const [deposited] = await getCpiEvents(connection, program, tx);
+    expect(deposited.name).toEqual("deposited");
+    expect(deposited.data.user.toBase58()).toEqual(user.publicKey.toBase58());
+    expect(deposited.data.underlyingIn.toString()).toEqual(amount.toString());
+    expect(deposited.data.internalLpMinted.toString()).toEqual(
+      userLpBalance.value.amount
+    );
+    expect(deposited.data.externalSharesReceived.toString()).toEqual(
+      vaultLpBalance.value.amount
+    );
+
+    const result = await getReturnData(connection, program, tx, "DepositResult");
+    expect(result.assetsIn.toString()).toEqual(amount.toString());
+    expect(result.lpMinted.toString()).toEqual(userLpBalance.value.amount);
+    expect(result.sharePrice.gtn(0)).toBe(true);
+  });
+
+  it("rejects a Jupiter deposit below min_lp_out", async () => {
+    const amount = new anchor.BN(10_000_000);
+    const depositContext = await getDepositContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
+    });
+
+    // fTokens never trade below one underlying unit, so asking for more
+    // shares than assets deposited must trip the bound.
+    await expect(
+      program.methods
+        .jupiterDeposit(jupiterVaultId, amount, amount.addn(1))
+        .accounts({
+          signer: user.publicKey,
+          inputToken: usdc,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+          lpToken: internalLpJupiter,
+          fTokenMint: depositContext.fTokenMint,
+          jupiterVault: depositContext.vault,
+          lending: depositContext.lending,
+          lendingAdmin: depositContext.lendingAdmin,
+          rewardsRateModel: depositContext.rewardsRateModel,
+          lendingSupplyPositionOnLiquidity: depositContext.lendingSupplyPositionOnLiquidity,
+          liquidity: depositContext.liquidity,
+          liquidityProgram: depositContext.liquidityProgram,
+          rateModel: depositContext.rateModel,
+          supplyTokenReservesLiquidity: depositContext.supplyTokenReservesLiquidity,
+        })
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+        .signers([user])
+        .rpc()
+    ).rejects.toThrow();
+  });
+
+  it("enforces and reports deposit caps", async () => {
+    const depositContext = await getDepositContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
+    });
+
+    await expect(
+      program.methods
+        .setDepositCaps(jupiterVaultId, new anchor.BN(1), new anchor.BN(1))
+        .accounts({ curator: protocolOwner.publicKey })
+        .signers([protocolOwner])
+        .rpc()
+    ).rejects.toThrow();
+
+    const depositCap = new anchor.BN(1_000_000_000);
+    const userDepositCap = new anchor.BN(150_000_000);
+
+    await program.methods
+      .setDepositCaps(jupiterVaultId, depositCap, userDepositCap)
+      .accounts({ curator: curator.publicKey })
+      .signers([curator])
+      .rpc();
+
+    const vaultLpAta = getAssociatedTokenAddressSync(
+      depositContext.fTokenMint,
+      jupiterVaultPda,
+      true,
+      TOKEN_PROGRAM_ID
+    );
+    const userLpAta = getAssociatedTokenAddressSync(
+      internalLpJupiter,
+      user.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID
+    );
+
+    const capacity = await program.methods
+      .depositCapacity(jupiterVaultId)
+      .accounts({
+        market: depositContext.lending,
+        externalLp: depositContext.fTokenMint,
+        vaultLpAta,
+        lpToken: internalLpJupiter,
+        userLpAta,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .view();
+
+    expect(capacity.vaultRemaining.lt(depositCap)).toBe(true);
+    expect(capacity.userRemaining.lt(userDepositCap)).toBe(true);
+
+    await program.methods
+      .setDepositCaps(jupiterVaultId, new anchor.BN(0), new anchor.BN(0))
+      .accounts({ curator: curator.publicKey })
+      .signers([curator])
+      .rpc();
   });
 
   it("burn LP and withdraw tokens from Jupiter", async () => {
@@ -272,7 +567,7 @@ describe("backyard-programs", () => {
     });
 
     const txBurn = await program.methods
-      .jupiterWithdraw(jupiterVaultId, amount)
+      .jupiterWithdraw(jupiterVaultId, amount, new anchor.BN(userLpBalance.value.amount))
       .accounts({
         signer: user.publicKey,
         outputToken: usdc,
@@ -293,7 +588,7 @@ describe("backyard-programs", () => {
       })
       .preInstructions([computeBudgetIx])
       .signers([user])
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     expect(txBurn).not.toBeNull();
 
@@ -308,125 +603,603 @@ describe("backyard-programs", () => {
     const after = Number(userOutputBalanceAfter.value.amount);
 
     expect(after).toBeGreaterThan(before);
+
+    const result = await getReturnData(connection, program, txBurn, "WithdrawResult");
+    expect(result.assetsOut.toString()).toEqual(amount.toString());
+    expect(result.lpBurned.toString()).toEqual(
+      (
+        BigInt(userLpBalance.value.amount) - BigInt(userLpBalanceAfter.value.amount)
+      ).toString()
+    );
   });
 
-  it("creates new lp token and vault PDA for Kamino", async () => {
-    const depositContext = await getKaminoDepositContext({
+  it("mints exact LP and redeems it on Jupiter", async () => {
+    const lpAmount = new anchor.BN(5_000_000);
+    const depositContext = await getDepositContext({
       asset: usdc,
       signer: user.publicKey,
       connection,
     });
-
-    const lpTokenKeypair = Keypair.generate();
-
-    const extensions = [ExtensionType.NonTransferable];
-    const mintLen = getMintLen(extensions);
-    const lamports = await connection.getMinimumBalanceForRentExemption(
-      mintLen
-    );
-
-    const createAccountIx = SystemProgram.createAccount({
-      fromPubkey: protocolOwner.publicKey,
-      newAccountPubkey: lpTokenKeypair.publicKey,
-      space: mintLen,
-      lamports,
-      programId: TOKEN_2022_PROGRAM_ID,
+    const withdrawContext = await getWithdrawContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
     });
-
-    const initializeNonTransferableIx =
-      createInitializeNonTransferableMintInstruction(
-        lpTokenKeypair.publicKey,
-        TOKEN_2022_PROGRAM_ID
-      );
-
-    const initializeMintIx = createInitializeMint2Instruction(
-      lpTokenKeypair.publicKey,
-      6,
-      kaminoVaultPda,
-      kaminoVaultPda,
+    const userLpAccount = getAssociatedTokenAddressSync(
+      internalLpJupiter,
+      user.publicKey,
+      false,
       TOKEN_2022_PROGRAM_ID
     );
+    const lpBefore = BigInt((await connection.getTokenAccountBalance(userLpAccount)).value.amount);
 
-    const setupTx = new Transaction().add(
-      createAccountIx,
-      initializeNonTransferableIx,
-      initializeMintIx
-    );
-
-    await sendAndConfirmTransaction(connection, setupTx, [
-      protocolOwner,
-      lpTokenKeypair,
-    ]);
+    await program.methods
+      .jupiterMint(jupiterVaultId, lpAmount, lpAmount.muln(2))
+      .accounts({
+        signer: user.publicKey,
+        inputToken: usdc,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+        lpToken: internalLpJupiter,
+        fTokenMint: depositContext.fTokenMint,
+        jupiterVault: depositContext.vault,
+        lending: depositContext.lending,
+        lendingAdmin: depositContext.lendingAdmin,
+        rewardsRateModel: depositContext.rewardsRateModel,
+        lendingSupplyPositionOnLiquidity: depositContext.lendingSupplyPositionOnLiquidity,
+        liquidity: depositContext.liquidity,
+        liquidityProgram: depositContext.liquidityProgram,
+        rateModel: depositContext.rateModel,
+        supplyTokenReservesLiquidity: depositContext.supplyTokenReservesLiquidity,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+      .signers([user])
+      .rpc();
 
-    internalLpKamino = lpTokenKeypair.publicKey;
+    const lpMinted = BigInt((await connection.getTokenAccountBalance(userLpAccount)).value.amount);
+    expect(lpMinted - lpBefore).toEqual(BigInt(lpAmount.toString()));
 
     const tx = await program.methods
-      .createVault(kaminoVaultId)
+      .jupiterRedeem(jupiterVaultId, lpAmount, new anchor.BN(1), false)
       .accounts({
-        token: usdc,
-        internalLp: internalLpKamino,
-        externalLp: depositContext.sharesMint,
+        signer: user.publicKey,
+        outputToken: usdc,
+        lpToken: internalLpJupiter,
         tokenProgram: TOKEN_PROGRAM_ID,
-        tokenProgram2022: TOKEN_2022_PROGRAM_ID
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+        fTokenMint: withdrawContext.fTokenMint,
+        jupiterVault: withdrawContext.vault,
+        lending: withdrawContext.lending,
+        lendingAdmin: withdrawContext.lendingAdmin,
+        lendingSupplyPositionOnLiquidity: withdrawContext.lendingSupplyPositionOnLiquidity,
+        liquidity: withdrawContext.liquidity,
+        liquidityProgram: withdrawContext.liquidityProgram,
+        rateModel: withdrawContext.rateModel,
+        rewardsRateModel: withdrawContext.rewardsRateModel,
+        supplyTokenReservesLiquidity: withdrawContext.supplyTokenReservesLiquidity,
+        claimAccount: withdrawContext.claimAccount,
       })
-      .signers([protocolOwner])
-      .rpc();
-
-    expect(tx).not.toBeNull();
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
 
-    const vaultAccount = await program.account.vault.fetch(kaminoVaultPda);
+    const lpAfter = BigInt((await connection.getTokenAccountBalance(userLpAccount)).value.amount);
+    expect(lpAfter).toEqual(lpBefore);
 
-    expect(vaultAccount.vaultId.toBase58()).toEqual(kaminoVaultId.toBase58());
+    const result = await getReturnData(connection, program, tx, "WithdrawResult");
+    expect(result.lpBurned.toString()).toEqual(lpAmount.toString());
+    expect(result.assetsOut.gtn(0)).toBe(true);
   });
 
-  it("deposit USDC to Kamino vault", async () => {
-
-    const amount = new anchor.BN(100000000);
-
-    const depositContext = await getKaminoDepositContext({
-      connection,
+  it("charges the performance fee only above the high-water mark", async () => {
+    await airdropIfRequired(connection, feeManager.publicKey, LAMPORTS_PER_SOL, LAMPORTS_PER_SOL / 2);
+    const depositContext = await getDepositContext({
       asset: usdc,
       signer: user.publicKey,
+      connection,
     });
-
-    const userUsdcAccount = getAssociatedTokenAddressSync(
-      usdc,
-      user.publicKey,
-      false,
+    const vaultLpAta = getAssociatedTokenAddressSync(
+      depositContext.fTokenMint,
+      jupiterVaultPda,
+      true,
       TOKEN_PROGRAM_ID
     );
-
-    const userInputBalanceBefore = await connection.getTokenAccountBalance(userUsdcAccount);
-    console.log("userInputBalanceBefore: ", userInputBalanceBefore.value.uiAmount);
-
-    await getOrCreateAssociatedTokenAccount(
-      connection,
-      user,
-      usdc,
-      kaminoVaultPda,
+    const feeRecipientLpAta = getAssociatedTokenAddressSync(
+      internalLpJupiter,
+      treasuryPda(jupiterVaultId),
       true,
-      undefined,
-      undefined,
-      TOKEN_PROGRAM_ID
+      TOKEN_2022_PROGRAM_ID
     );
+    const setFees = (performanceFeeBps: number, managementFeeBps: number, signer: Keypair) =>
+      program.methods
+        .setFees(jupiterVaultId, performanceFeeBps, managementFeeBps, 0, 0)
+        .accounts({
+          feeManager: signer.publicKey,
+          market: depositContext.lending,
+          externalLp: depositContext.fTokenMint,
+          vaultLpAta,
+          lpToken: internalLpJupiter,
+          token: usdc,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([signer])
+        .rpc();
+    const accrue = () =>
+      program.methods
+        .accrue(jupiterVaultId)
+        .accounts({
+          market: depositContext.lending,
+          externalLp: depositContext.fTokenMint,
+          vaultLpAta,
+          lpToken: internalLpJupiter,
+          feeRecipientLpAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+    const feeBalance = async () =>
+      BigInt((await connection.getTokenAccountBalance(feeRecipientLpAta)).value.amount);
+
+    await expect(setFees(1_000, 0, user)).rejects.toThrow();
+    await expect(setFees(5_001, 0, feeManager)).rejects.toThrow();
+
+    await setFees(1_000, 0, feeManager);
+    const tx = await accrue();
+
+    const vault = await program.account.vault.fetch(jupiterVaultPda);
+    expect(vault.performanceFeeBps).toEqual(1_000);
+    expect(vault.feeRecipient.toBase58()).toEqual(treasuryPda(jupiterVaultId).toBase58());
+
+    // Once accrued, holders are priced at or below the mark, never above it.
+    const lpMint = await getMint(connection, internalLpJupiter, "confirmed", TOKEN_2022_PROGRAM_ID);
+    const sharePrice =
+      (BigInt(vault.totalAssets.toString()) * 1_000_000_000_000n) / lpMint.supply;
+    expect(BigInt(vault.highWaterMark.toString()) >= sharePrice).toBe(true);
+
+    const feeLp = await feeBalance();
+    const accrued = (await getCpiEvents(connection, program, tx)).filter(
+      (event) => event.name === "feesAccrued"
+    );
+    expect(
+      accrued.reduce((sum, event) => sum + BigInt(event.data.performanceFeeLp.toString()), 0n)
+    ).toEqual(feeLp);
+
+    // Nothing has been earned above the new mark, so accruing again is free.
+    await accrue();
+    expect(await feeBalance()).toEqual(feeLp);
 
-    await getOrCreateAssociatedTokenAccount(
+    await setFees(0, 0, feeManager);
+  });
+
+  it("accrues the management fee over time", async () => {
+    const depositContext = await getDepositContext({
+      asset: usdc,
+      signer: user.publicKey,
       connection,
-      user,
-      depositContext.sharesMint,
-      kaminoVaultPda,
+    });
+    const vaultLpAta = getAssociatedTokenAddressSync(
+      depositContext.fTokenMint,
+      jupiterVaultPda,
       true,
-      undefined,
-      undefined,
       TOKEN_PROGRAM_ID
     );
+    const feeRecipientLpAta = getAssociatedTokenAddressSync(
+      internalLpJupiter,
+      treasuryPda(jupiterVaultId),
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const setFees = (managementFeeBps: number) =>
+      program.methods
+        .setFees(jupiterVaultId, 0, managementFeeBps, 0, 0)
+        .accounts({
+          feeManager: feeManager.publicKey,
+          market: depositContext.lending,
+          externalLp: depositContext.fTokenMint,
+          vaultLpAta,
+          lpToken: internalLpJupiter,
+          token: usdc,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([feeManager])
+        .rpc();
+    const accrue = () =>
+      program.methods
+        .accrue(jupiterVaultId)
+        .accounts({
+          market: depositContext.lending,
+          externalLp: depositContext.fTokenMint,
+          vaultLpAta,
+          lpToken: internalLpJupiter,
+          feeRecipientLpAta,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .rpc({ commitment: "confirmed" });
+
+    await expect(setFees(501)).rejects.toThrow();
+
+    await accrue();
+    await setFees(500);
+    const before = await program.account.vault.fetch(jupiterVaultPda);
+    expect(before.managementFeeBps).toEqual(500);
+
+    await new Promise((resolve) => setTimeout(resolve, 2_000));
+    const tx = await accrue();
+
+    const after = await program.account.vault.fetch(jupiterVaultPda);
+    let managementFeeLp = 0n;
+    for (const event of await getCpiEvents(connection, program, tx)) {
+      if (event.name === "feesAccrued") {
+        expect(event.data.performanceFeeLp.toString()).toEqual("0");
+        managementFeeLp += BigInt(event.data.managementFeeLp.toString());
+      }
+    }
+    // Time whose fee rounds to zero is carried forward, not forfeited.
+    if (managementFeeLp > 0n) {
+      expect(after.lastFeeAccrual.gt(before.lastFeeAccrual)).toBe(true);
+    } else {
+      expect(after.lastFeeAccrual.eq(before.lastFeeAccrual)).toBe(true);
+    }
+
+    await accrue();
+    await setFees(0);
+  });
+
+  it("charges entry and exit fees within the admin bounds", async () => {
+    const amount = new anchor.BN(10_000_000);
+    const depositContext = await getDepositContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
+    });
+    const withdrawContext = await getWithdrawContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
+    });
+    const feeTokenAccount = getAssociatedTokenAddressSync(usdc, treasuryPda(jupiterVaultId), true);
+    const setFees = (entryFeeBps: number, exitFeeBps: number) =>
+      program.methods
+        .setFees(jupiterVaultId, 0, 0, entryFeeBps, exitFeeBps)
+        .accounts({
+          feeManager: feeManager.publicKey,
+          market: depositContext.lending,
+          externalLp: depositContext.fTokenMint,
+          vaultLpAta: getAssociatedTokenAddressSync(
+            depositContext.fTokenMint,
+            jupiterVaultPda,
+            true,
+            TOKEN_PROGRAM_ID
+          ),
+          lpToken: internalLpJupiter,
+          token: usdc,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([feeManager])
+        .rpc();
+    const feeBalance = async () =>
+      BigInt((await connection.getTokenAccountBalance(feeTokenAccount)).value.amount);
+
+    // Bounds start at zero, so the fee manager cannot charge until the admin
+    // allows it.
+    await expect(setFees(100, 100)).rejects.toThrow();
+    await expect(
+      program.methods
+        .setFeeBounds(100, 100)
+        .accounts({ admin: user.publicKey })
+        .signers([user])
+        .rpc()
+    ).rejects.toThrow();
+
+    await program.methods
+      .setFeeBounds(100, 100)
+      .accounts({ admin: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
+    await expect(setFees(101, 100)).rejects.toThrow();
+    await setFees(100, 100);
+
+    const deposit = () =>
+      program.methods
+        .jupiterDeposit(jupiterVaultId, amount, new anchor.BN(1))
+        .accounts({
+          signer: user.publicKey,
+          inputToken: usdc,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+          lpToken: internalLpJupiter,
+          fTokenMint: depositContext.fTokenMint,
+          jupiterVault: depositContext.vault,
+          lending: depositContext.lending,
+          lendingAdmin: depositContext.lendingAdmin,
+          rewardsRateModel: depositContext.rewardsRateModel,
+          lendingSupplyPositionOnLiquidity: depositContext.lendingSupplyPositionOnLiquidity,
+          liquidity: depositContext.liquidity,
+          liquidityProgram: depositContext.liquidityProgram,
+          rateModel: depositContext.rateModel,
+          supplyTokenReservesLiquidity: depositContext.supplyTokenReservesLiquidity,
+          feeTokenAccount,
+        })
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+    const depositedEvent = async (tx: string) =>
+      (await getCpiEvents(connection, program, tx)).find((event) => event.name === "deposited")!;
+
+    const feeBefore = await feeBalance();
+    const depositTx = await deposit();
+
+    const deposited = await depositedEvent(depositTx);
+    expect(deposited.data.fee.toString()).toEqual("100000");
+    expect(deposited.data.underlyingIn.add(deposited.data.fee).toString()).toEqual(
+      amount.toString()
+    );
+    const feeAfterDeposit = await feeBalance();
+    expect(feeAfterDeposit - feeBefore).toEqual(100_000n);
+
+    const redeemTx = await program.methods
+      .jupiterRedeem(jupiterVaultId, deposited.data.internalLpMinted, new anchor.BN(1), false)
+      .accounts({
+        signer: user.publicKey,
+        outputToken: usdc,
+        lpToken: internalLpJupiter,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+        fTokenMint: withdrawContext.fTokenMint,
+        jupiterVault: withdrawContext.vault,
+        lending: withdrawContext.lending,
+        lendingAdmin: withdrawContext.lendingAdmin,
+        lendingSupplyPositionOnLiquidity: withdrawContext.lendingSupplyPositionOnLiquidity,
+        liquidity: withdrawContext.liquidity,
+        liquidityProgram: withdrawContext.liquidityProgram,
+        rateModel: withdrawContext.rateModel,
+        rewardsRateModel: withdrawContext.rewardsRateModel,
+        supplyTokenReservesLiquidity: withdrawContext.supplyTokenReservesLiquidity,
+        claimAccount: withdrawContext.claimAccount,
+        feeTokenAccount,
+      })
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    const withdrawn = (await getCpiEvents(connection, program, redeemTx)).find(
+      (event) => event.name === "withdrawn"
+    )!;
+    expect(withdrawn.data.fee.gtn(0)).toBe(true);
+    expect((await feeBalance()) - feeAfterDeposit).toEqual(
+      BigInt(withdrawn.data.fee.toString())
+    );
+
+    // Lowering a bound caps fees already set above it.
+    await program.methods
+      .setFeeBounds(50, 100)
+      .accounts({ admin: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
+    const capped = await depositedEvent(await deposit());
+    expect(capped.data.fee.toString()).toEqual("50000");
+
+    await setFees(0, 0);
+  });
+
+  it("claims treasury fees split between destinations by weight", async () => {
+    const withdrawContext = await getWithdrawContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
+    });
+    const treasuryTokenAta = getAssociatedTokenAddressSync(usdc, treasuryPda(jupiterVaultId), true);
+    const userUsdcAta = getAssociatedTokenAddressSync(usdc, user.publicKey);
+    const feeManagerUsdcAta = await createAssociatedTokenAccountIdempotent(
+      connection,
+      feeManager,
+      usdc,
+      feeManager.publicKey
+    );
+    const balance = async (account: PublicKey) =>
+      BigInt((await connection.getTokenAccountBalance(account)).value.amount);
+
+    await expect(
+      program.methods
+        .setFeeSplit(jupiterVaultId, [{ destination: userUsdcAta, weight: 1 }])
+        .accounts({ feeManager: user.publicKey })
+        .signers([user])
+        .rpc()
+    ).rejects.toThrow();
+    await expect(
+      program.methods
+        .setFeeSplit(jupiterVaultId, [{ destination: userUsdcAta, weight: 0 }])
+        .accounts({ feeManager: feeManager.publicKey })
+        .signers([feeManager])
+        .rpc()
+    ).rejects.toThrow();
+
+    await program.methods
+      .setFeeSplit(jupiterVaultId, [
+        { destination: feeManagerUsdcAta, weight: 3 },
+        { destination: userUsdcAta, weight: 1 },
+      ])
+      .accounts({ feeManager: feeManager.publicKey })
+      .signers([feeManager])
+      .rpc();
+
+    const treasuryBefore = await balance(treasuryTokenAta);
+    const feeManagerBefore = await balance(feeManagerUsdcAta);
+    const userBefore = await balance(userUsdcAta);
+
+    const tx = await program.methods
+      .jupiterClaimFees(jupiterVaultId)
+      .accounts({
+        feeManager: feeManager.publicKey,
+        outputToken: usdc,
+        lpToken: internalLpJupiter,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+        fTokenMint: withdrawContext.fTokenMint,
+        jupiterVault: withdrawContext.vault,
+        lending: withdrawContext.lending,
+        lendingAdmin: withdrawContext.lendingAdmin,
+        lendingSupplyPositionOnLiquidity: withdrawContext.lendingSupplyPositionOnLiquidity,
+        liquidity: withdrawContext.liquidity,
+        liquidityProgram: withdrawContext.liquidityProgram,
+        rateModel: withdrawContext.rateModel,
+        rewardsRateModel: withdrawContext.rewardsRateModel,
+        supplyTokenReservesLiquidity: withdrawContext.supplyTokenReservesLiquidity,
+        claimAccount: withdrawContext.claimAccount,
+      })
+      .remainingAccounts([
+        { pubkey: feeManagerUsdcAta, isSigner: false, isWritable: true },
+        { pubkey: userUsdcAta, isSigner: false, isWritable: true },
+      ])
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+      .signers([feeManager])
+      .rpc({ commitment: "confirmed" });
+
+    const claimed = (await getCpiEvents(connection, program, tx)).find(
+      (event) => event.name === "feesClaimed"
+    )!;
+    const amount = BigInt(claimed.data.amount.toString());
+    expect(amount >= treasuryBefore).toBe(true);
+    expect(await balance(treasuryTokenAta)).toEqual(0n);
+
+    const toFeeManager = (await balance(feeManagerUsdcAta)) - feeManagerBefore;
+    expect(toFeeManager).toEqual((amount * 3n) / 4n);
+    expect((await balance(userUsdcAta)) - userBefore).toEqual(amount - toFeeManager);
+  });
+
+  it("sweeps stranded tokens but never the fTokens backing LP", async () => {
+    const depositContext = await getDepositContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
+    });
+    const userUsdcAta = getAssociatedTokenAddressSync(usdc, user.publicKey, false, TOKEN_PROGRAM_ID);
+    const vaultTokenAta = getAssociatedTokenAddressSync(usdc, jupiterVaultPda, true, TOKEN_PROGRAM_ID);
+    const vaultLpAta = getAssociatedTokenAddressSync(
+      depositContext.fTokenMint,
+      jupiterVaultPda,
+      true,
+      TOKEN_PROGRAM_ID
+    );
+
+    const treasuryUsdcAta = getAssociatedTokenAddressSync(usdc, treasuryPda(jupiterVaultId), true);
+    const treasuryFTokenAta = getAssociatedTokenAddressSync(
+      depositContext.fTokenMint,
+      treasuryPda(jupiterVaultId),
+      true
+    );
+
+    await transferChecked(connection, user, userUsdcAta, usdc, vaultTokenAta, user, 1_000_000, 6);
+    const before = BigInt((await connection.getTokenAccountBalance(treasuryUsdcAta)).value.amount);
+
+    await expect(
+      program.methods
+        .sweep(jupiterVaultId)
+        .accounts({
+          admin: user.publicKey,
+          mint: usdc,
+          source: vaultTokenAta,
+          destination: treasuryUsdcAta,
+          lpToken: internalLpJupiter,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([user])
+        .rpc()
+    ).rejects.toThrow();
+
+    await program.methods
+      .sweep(jupiterVaultId)
+      .accounts({
+        admin: protocolOwner.publicKey,
+        mint: usdc,
+        source: vaultTokenAta,
+        destination: treasuryUsdcAta,
+        lpToken: internalLpJupiter,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([protocolOwner])
+      .rpc();
+
+    const after = BigInt((await connection.getTokenAccountBalance(treasuryUsdcAta)).value.amount);
+    expect(after - before).toEqual(1_000_000n);
+
+    await expect(
+      program.methods
+        .sweep(jupiterVaultId)
+        .accounts({
+          admin: protocolOwner.publicKey,
+          mint: depositContext.fTokenMint,
+          source: vaultLpAta,
+          destination: treasuryFTokenAta,
+          lpToken: internalLpJupiter,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([protocolOwner])
+        .rpc()
+    ).rejects.toThrow();
+  });
+
+  it("creates new lp token and vault PDA for Kamino", async () => {
+    const depositContext = await getKaminoDepositContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
+    });
+
+    const tx = await program.methods
+      .createVault(
+        kaminoVaultId,
+        { kaminoVault: {} },
+        "Backyard Kamino USDC",
+        "bykUSDC",
+        "https://backyard.finance/lp/kamino-usdc.json"
+      )
+      .accounts({
+        payer: protocolOwner.publicKey,
+        admin: protocolOwner.publicKey,
+        token: usdc,
+        externalLp: depositContext.sharesMint,
+        market: depositContext.vaultState,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([protocolOwner])
+      .rpc();
+
+    expect(tx).not.toBeNull();
+
+    const vaultAccount = await program.account.vault.fetch(kaminoVaultPda);
+
+    expect(vaultAccount.vaultId.toBase58()).toEqual(kaminoVaultId.toBase58());
+    expect(vaultAccount.protocol).toEqual({ kaminoVault: {} });
+    expect(vaultAccount.market.toBase58()).toEqual(depositContext.vaultState.toBase58());
+    expect(vaultAccount.internalLp.toBase58()).toEqual(internalLpKamino.toBase58());
+  });
+
+  it("deposit USDC to Kamino vault", async () => {
+
+    const amount = new anchor.BN(100000000);
+
+    const depositContext = await getKaminoDepositContext({
+      connection,
+      asset: usdc,
+      signer: user.publicKey,
+    });
+
+    const userUsdcAccount = getAssociatedTokenAddressSync(
+      usdc,
+      user.publicKey,
+      false,
+      TOKEN_PROGRAM_ID
+    );
+
+    const userInputBalanceBefore = await connection.getTokenAccountBalance(userUsdcAccount);
+    console.log("userInputBalanceBefore: ", userInputBalanceBefore.value.uiAmount);
 
     const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
       units: 600_000,
     });
 
     const tx = await program.methods
-      .kaminoVaultDeposit(kaminoVaultId, amount)
+      .kaminoVaultDeposit(kaminoVaultId, amount, new anchor.BN(1))
       .accounts({
         signer: user.publicKey,
         inputToken: usdc,
@@ -435,7 +1208,7 @@ describe("backyard-programs", () => {
         baseVaultAuthority: depositContext.baseVaultAuthority,
         sharesMint: depositContext.sharesMint,
         lpToken: internalLpKamino,
-        eventAuthority: depositContext.eventAuthority,
+        kaminoEventAuthority: depositContext.eventAuthority,
         klendProgram: depositContext.klendProgram,
         tokenProgram: TOKEN_PROGRAM_ID,
         tokenProgram2022: TOKEN_2022_PROGRAM_ID,
@@ -443,7 +1216,7 @@ describe("backyard-programs", () => {
       .remainingAccounts(depositContext.remainingAccounts)
       .preInstructions([computeBudgetIx])
       .signers([user])
-      .rpc();
+      .rpc({ commitment: "confirmed" });
 
     expect(tx).not.toBeNull();
 
@@ -467,10 +1240,26 @@ describe("backyard-programs", () => {
     );
     const userLpBalance = await connection.getTokenAccountBalance(userLpAccount);
     expect(Number(userLpBalance.value.amount)).toBeGreaterThan(0);
-    expect(vaultLpBalance.value.amount).toEqual(userLpBalance.value.amount);
+    // The first deposit into an empty vault mints one LP per underlying unit
+    // the external shares are worth, rounded down.
+    expect(BigInt(userLpBalance.value.amount) <= BigInt(amount.toString())).toBe(true);
+
+    // Whatever Kamino did not take is refunded, so the user is only charged
+    // for the underlying actually deposited.
+    const [deposited] = await getCpiEvents(connection, program, tx);
+    expect(deposited.data.underlyingIn.add(deposited.data.refunded).toString()).toEqual(
+      amount.toString()
+    );
+    expect(
+      BigInt(userInputBalanceBefore.value.amount) - BigInt(userInputBalanceAfter.value.amount)
+    ).toEqual(BigInt(deposited.data.underlyingIn.toString()));
+    expect(deposited.data.externalSharesReceived.toString()).toEqual(
+      vaultLpBalance.value.amount
+    );
+    expect(deposited.data.internalLpMinted.toString()).toEqual(userLpBalance.value.amount);
   });
 
-  it("withdraw USDC from Kamino vault", async () => {
+  it("redeem LP for USDC from Kamino vault", async () => {
     const withdrawContext = await getKaminoWithdrawContext({
       connection,
       asset: usdc,
@@ -499,34 +1288,387 @@ describe("backyard-programs", () => {
     const userUsdcBalanceBefore = await connection.getTokenAccountBalance(userUsdcAccount);
     console.log("User USDC balance before:", userUsdcBalanceBefore.value.uiAmount);
 
-    await getOrCreateAssociatedTokenAccount(
+    const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
+      units: 800_000,
+    });
+
+    const instruction = await program.methods
+      .kaminoVaultRedeem(kaminoVaultId, lpAmountToWithdraw, new anchor.BN(1), false)
+      .accounts({
+        signer: user.publicKey,
+        outputToken: usdc,
+        lpToken: internalLpKamino,
+        vaultState: withdrawContext.vaultState,
+        reserve: withdrawContext.reserve,
+        tokenVault: withdrawContext.tokenVault,
+        baseVaultAuthority: withdrawContext.baseVaultAuthority,
+        kaminoEventAuthority: withdrawContext.eventAuthority,
+        sharesMint: withdrawContext.sharesMint,
+        lendingMarket: withdrawContext.lendingMarket,
+        lendingMarketAuthority: withdrawContext.lendingMarketAuthority,
+        reserveLiquiditySupply: withdrawContext.reserveLiquiditySupply,
+        reserveCollateralMint: withdrawContext.reserveCollateralMint,
+        ctokenVault: withdrawContext.ctokenVault,
+        klendProgram: withdrawContext.klendProgram,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      })
+      .remainingAccounts(withdrawContext.remainingAccounts)
+      .instruction();
+
+    const messageV0 = new TransactionMessage({
+      payerKey: user.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash,
+      instructions: [computeBudgetIx, instruction],
+    }).compileToV0Message();
+
+    const transaction = new VersionedTransaction(messageV0);
+    transaction.sign([user]);
+
+    const tx = await connection.sendTransaction(transaction);
+
+    expect(tx).not.toBeNull();
+
+    const userLpBalanceAfter = await connection.getTokenAccountBalance(userLpAccount);
+    console.log("User LP balance after:", userLpBalanceAfter.value.uiAmount);
+
+    const userUsdcBalanceAfter = await connection.getTokenAccountBalance(userUsdcAccount);
+    console.log("User USDC balance after:", userUsdcBalanceAfter.value.uiAmount);
+
+    expect(Number(userUsdcBalanceAfter.value.amount)).toBeGreaterThan(
+      Number(userUsdcBalanceBefore.value.amount)
+    );
+  });
+
+  it("mints exact LP on Kamino", async () => {
+    const lpAmount = new anchor.BN(5_000_000);
+    const maxUnderlyingIn = lpAmount.muln(2);
+    const depositContext = await getKaminoDepositContext({
       connection,
-      user,
-      usdc,
+      asset: usdc,
+      signer: user.publicKey,
+    });
+    const userUsdcAccount = getAssociatedTokenAddressSync(usdc, user.publicKey, false, TOKEN_PROGRAM_ID);
+    const userLpAccount = getAssociatedTokenAddressSync(
+      internalLpKamino,
+      user.publicKey,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const vaultSharesAccount = getAssociatedTokenAddressSync(
+      depositContext.sharesMint,
       kaminoVaultPda,
       true,
-      undefined,
-      undefined,
       TOKEN_PROGRAM_ID
     );
+    const balance = async (account: PublicKey) =>
+      BigInt((await connection.getTokenAccountBalance(account, "confirmed")).value.amount);
+
+    const usdcBefore = await balance(userUsdcAccount);
+    const lpBefore = await balance(userLpAccount);
+    const sharesBefore = await balance(vaultSharesAccount);
 
-    await getOrCreateAssociatedTokenAccount(
+    const tx = await program.methods
+      .kaminoVaultMint(kaminoVaultId, lpAmount, maxUnderlyingIn)
+      .accounts({
+        signer: user.publicKey,
+        inputToken: usdc,
+        vaultState: depositContext.vaultState,
+        tokenVault: depositContext.tokenVault,
+        baseVaultAuthority: depositContext.baseVaultAuthority,
+        sharesMint: depositContext.sharesMint,
+        lpToken: internalLpKamino,
+        kaminoEventAuthority: depositContext.eventAuthority,
+        klendProgram: depositContext.klendProgram,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      })
+      .remainingAccounts(depositContext.remainingAccounts)
+      .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+      .signers([user])
+      .rpc({ commitment: "confirmed" });
+
+    const deposited = (await getCpiEvents(connection, program, tx)).find(
+      (event) => event.name === "deposited"
+    )!;
+    const underlyingIn = BigInt(deposited.data.underlyingIn.toString());
+    const lpMinted = BigInt(deposited.data.internalLpMinted.toString());
+
+    // The deposit is sized from the recorded AUM, so it mints exactly the LP
+    // asked for without exceeding the cap.
+    expect(lpMinted).toEqual(BigInt(lpAmount.toString()));
+    expect(underlyingIn <= BigInt(maxUnderlyingIn.toString())).toBe(true);
+    expect((await balance(userLpAccount)) - lpBefore).toEqual(lpMinted);
+    expect(usdcBefore - (await balance(userUsdcAccount))).toEqual(underlyingIn);
+    expect((await balance(vaultSharesAccount)) - sharesBefore).toEqual(
+      BigInt(deposited.data.externalSharesReceived.toString())
+    );
+  });
+
+  it("withdraws exact USDC from Kamino", async () => {
+    const outputAmount = new anchor.BN(2_000_000);
+    const withdrawContext = await getKaminoWithdrawContext({
       connection,
-      user,
+      asset: usdc,
+      signer: user.publicKey,
+    });
+    const userUsdcAccount = getAssociatedTokenAddressSync(usdc, user.publicKey, false, TOKEN_PROGRAM_ID);
+    const userLpAccount = getAssociatedTokenAddressSync(
+      internalLpKamino,
+      user.publicKey,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const vaultSharesAccount = getAssociatedTokenAddressSync(
       withdrawContext.sharesMint,
       kaminoVaultPda,
       true,
-      undefined,
-      undefined,
       TOKEN_PROGRAM_ID
     );
+    const balance = async (account: PublicKey) =>
+      BigInt((await connection.getTokenAccountBalance(account, "confirmed")).value.amount);
 
-    const computeBudgetIx = ComputeBudgetProgram.setComputeUnitLimit({
-      units: 800_000,
+    const usdcBefore = await balance(userUsdcAccount);
+    const lpBefore = await balance(userLpAccount);
+    const sharesBefore = await balance(vaultSharesAccount);
+
+    const instruction = await program.methods
+      .kaminoVaultWithdraw(kaminoVaultId, outputAmount, new anchor.BN(lpBefore.toString()))
+      .accounts({
+        signer: user.publicKey,
+        outputToken: usdc,
+        lpToken: internalLpKamino,
+        vaultState: withdrawContext.vaultState,
+        reserve: withdrawContext.reserve,
+        tokenVault: withdrawContext.tokenVault,
+        baseVaultAuthority: withdrawContext.baseVaultAuthority,
+        kaminoEventAuthority: withdrawContext.eventAuthority,
+        sharesMint: withdrawContext.sharesMint,
+        lendingMarket: withdrawContext.lendingMarket,
+        lendingMarketAuthority: withdrawContext.lendingMarketAuthority,
+        reserveLiquiditySupply: withdrawContext.reserveLiquiditySupply,
+        reserveCollateralMint: withdrawContext.reserveCollateralMint,
+        ctokenVault: withdrawContext.ctokenVault,
+        klendProgram: withdrawContext.klendProgram,
+        tokenProgram: TOKEN_PROGRAM_ID,
+        tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+      })
+      .remainingAccounts(withdrawContext.remainingAccounts)
+      .instruction();
+
+    const messageV0 = new TransactionMessage({
+      payerKey: user.publicKey,
+      recentBlockhash: (await connection.getLatestBlockhash()).blockhash,
+      instructions: [ComputeBudgetProgram.setComputeUnitLimit({ units: 800_000 }), instruction],
+    }).compileToV0Message();
+    const transaction = new VersionedTransaction(messageV0);
+    transaction.sign([user]);
+    const tx = await connection.sendTransaction(transaction);
+    await connection.confirmTransaction(tx, "confirmed");
+
+    const withdrawn = (await getCpiEvents(connection, program, tx)).find(
+      (event) => event.name === "withdrawn"
+    )!;
+    const underlyingOut = BigInt(withdrawn.data.underlyingOut.toString());
+    const lpBurned = BigInt(withdrawn.data.lpBurned.toString());
+    const requested = BigInt(outputAmount.toString());
+
+    // Shares are sized from the recorded AUM with a 10 bps buffer, so the
+    // payout covers the request and overshoots it by little more than that.
+    expect(underlyingOut >= requested).toBe(true);
+    expect(underlyingOut <= requested + (requested * 20n) / 10_000n).toBe(true);
+    expect((await balance(userUsdcAccount)) - usdcBefore).toEqual(underlyingOut);
+    expect(lpBefore - (await balance(userLpAccount))).toEqual(lpBurned);
+    expect(sharesBefore - (await balance(vaultSharesAccount))).toEqual(
+      BigInt(withdrawn.data.externalSharesRedeemed.toString())
+    );
+  });
+
+  it("claims Kamino treasury fees split between destinations by weight", async () => {
+    const depositContext = await getKaminoDepositContext({
+      connection,
+      asset: usdc,
+      signer: user.publicKey,
     });
+    const withdrawContext = await getKaminoWithdrawContext({
+      connection,
+      asset: usdc,
+      signer: user.publicKey,
+    });
+    const vaultSharesAccount = getAssociatedTokenAddressSync(
+      depositContext.sharesMint,
+      kaminoVaultPda,
+      true,
+      TOKEN_PROGRAM_ID
+    );
+    const treasuryTokenAta = getAssociatedTokenAddressSync(usdc, treasuryPda(kaminoVaultId), true);
+    const treasuryLpAta = getAssociatedTokenAddressSync(
+      internalLpKamino,
+      treasuryPda(kaminoVaultId),
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
+    const feeManagerUsdcAta = await createAssociatedTokenAccountIdempotent(
+      connection,
+      feeManager,
+      usdc,
+      feeManager.publicKey
+    );
+    const curatorUsdcAta = await createAssociatedTokenAccountIdempotent(
+      connection,
+      feeManager,
+      usdc,
+      curator.publicKey
+    );
+    const balance = async (account: PublicKey) =>
+      BigInt((await connection.getTokenAccountBalance(account, "confirmed")).value.amount);
+    const setFees = (managementFeeBps: number, entryFeeBps: number) =>
+      program.methods
+        .setFees(kaminoVaultId, 0, managementFeeBps, entryFeeBps, 0)
+        .accounts({
+          feeManager: feeManager.publicKey,
+          market: depositContext.vaultState,
+          externalLp: depositContext.sharesMint,
+          vaultLpAta: vaultSharesAccount,
+          lpToken: internalLpKamino,
+          token: usdc,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([feeManager])
+        .rpc({ commitment: "confirmed" });
+    // Deposits with the entry fee in force and returns the fee it paid the treasury.
+    const deposit = async () => {
+      const tx = await program.methods
+        .kaminoVaultDeposit(kaminoVaultId, new anchor.BN(10_000_000), new anchor.BN(1))
+        .accounts({
+          signer: user.publicKey,
+          inputToken: usdc,
+          vaultState: depositContext.vaultState,
+          tokenVault: depositContext.tokenVault,
+          baseVaultAuthority: depositContext.baseVaultAuthority,
+          sharesMint: depositContext.sharesMint,
+          lpToken: internalLpKamino,
+          kaminoEventAuthority: depositContext.eventAuthority,
+          klendProgram: depositContext.klendProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+          feeTokenAccount: treasuryTokenAta,
+        })
+        .remainingAccounts(depositContext.remainingAccounts)
+        .preInstructions([ComputeBudgetProgram.setComputeUnitLimit({ units: 600_000 })])
+        .signers([user])
+        .rpc({ commitment: "confirmed" });
+      const deposited = (await getCpiEvents(connection, program, tx)).find(
+        (event) => event.name === "deposited"
+      )!;
+      return BigInt(deposited.data.fee.toString());
+    };
+    const claim = async () => {
+      const instruction = await program.methods
+        .kaminoVaultClaimFees(kaminoVaultId)
+        .accounts({
+          feeManager: feeManager.publicKey,
+          outputToken: usdc,
+          lpToken: internalLpKamino,
+          vaultState: withdrawContext.vaultState,
+          reserve: withdrawContext.reserve,
+          tokenVault: withdrawContext.tokenVault,
+          baseVaultAuthority: withdrawContext.baseVaultAuthority,
+          kaminoEventAuthority: withdrawContext.eventAuthority,
+          sharesMint: withdrawContext.sharesMint,
+          lendingMarket: withdrawContext.lendingMarket,
+          lendingMarketAuthority: withdrawContext.lendingMarketAuthority,
+          reserveLiquiditySupply: withdrawContext.reserveLiquiditySupply,
+          reserveCollateralMint: withdrawContext.reserveCollateralMint,
+          ctokenVault: withdrawContext.ctokenVault,
+          klendProgram: withdrawContext.klendProgram,
+          tokenProgram: TOKEN_PROGRAM_ID,
+          tokenProgram2022: TOKEN_2022_PROGRAM_ID,
+        })
+        .remainingAccounts([
+          { pubkey: feeManagerUsdcAta, isSigner: false, isWritable: true },
+          { pubkey: curatorUsdcAta, isSigner: false, isWritable: true },
+          ...withdrawContext.remainingAccounts,
+        ])
+        .instruction();
+
+      const messageV0 = new TransactionMessage({
+        payerKey: feeManager.publicKey,
+        recentBlockhash: (await connection.getLatestBlockhash()).blockhash,
+        instructions: [ComputeBudgetProgram.setComputeUnitLimit({ units: 800_000 }), instruction],
+      }).compileToV0Message();
+      const transaction = new VersionedTransaction(messageV0);
+      transaction.sign([feeManager]);
+      const tx = await connection.sendTransaction(transaction);
+      await connection.confirmTransaction(tx, "confirmed");
+
+      return (await getCpiEvents(connection, program, tx)).find(
+        (event) => event.name === "feesClaimed"
+      )!;
+    };
+
+    await program.methods
+      .setFeeBounds(100, 100)
+      .accounts({ admin: protocolOwner.publicKey })
+      .signers([protocolOwner])
+      .rpc();
+    await program.methods
+      .setFeeSplit(kaminoVaultId, [
+        { destination: feeManagerUsdcAta, weight: 3 },
+        { destination: curatorUsdcAta, weight: 1 },
+      ])
+      .accounts({ feeManager: feeManager.publicKey })
+      .signers([feeManager])
+      .rpc();
+
+    // Charge an entry fee and accrue a management fee while turning both off,
+    // then claim to empty the treasury of whatever LP and underlying it holds.
+    await setFees(500, 100);
+    expect(await deposit()).toBeGreaterThan(0n);
+    await setFees(0, 0);
+
+    const treasuryLpBefore = await balance(treasuryLpAta);
+    const drained = await claim();
+    expect(BigInt(drained.data.lpRedeemed.toString())).toEqual(treasuryLpBefore);
+    expect(await balance(treasuryLpAta)).toEqual(0n);
+    expect(await balance(treasuryTokenAta)).toEqual(0n);
+
+    // With fees off, the treasury now holds exactly one entry fee and no LP.
+    await setFees(0, 100);
+    const fee = await deposit();
+    await setFees(0, 0);
+    expect(await balance(treasuryTokenAta)).toEqual(fee);
+    expect(await balance(treasuryLpAta)).toEqual(0n);
+
+    const feeManagerBefore = await balance(feeManagerUsdcAta);
+    const curatorBefore = await balance(curatorUsdcAta);
+    const sharesBeforeClaim = await balance(vaultSharesAccount);
+    const claimed = await claim();
+
+    expect(claimed.data.lpRedeemed.toString()).toEqual("0");
+    expect(BigInt(claimed.data.amount.toString())).toEqual(fee);
+    expect(await balance(treasuryTokenAta)).toEqual(0n);
+    expect(await balance(vaultSharesAccount)).toEqual(sharesBeforeClaim);
+    // Weights 3:1, rounded down, with the remainder going to the last split.
+    expect((await balance(feeManagerUsdcAta)) - feeManagerBefore).toEqual((fee * 3n) / 4n);
+    expect((await balance(curatorUsdcAta)) - curatorBefore).toEqual(fee - (fee * 3n) / 4n);
+  });
+
+  it("redeems the whole Kamino position and closes the LP account", async () => {
+    const withdrawContext = await getKaminoWithdrawContext({
+      connection,
+      asset: usdc,
+      signer: user.publicKey,
+    });
+    const userLpAccount = getAssociatedTokenAddressSync(
+      internalLpKamino,
+      user.publicKey,
+      true,
+      TOKEN_2022_PROGRAM_ID
+    );
 
     const instruction = await program.methods
-      .kaminoVaultWithdraw(kaminoVaultId, lpAmountToWithdraw)
+      .kaminoVaultRedeem(kaminoVaultId, new anchor.BN("18446744073709551615"), new anchor.BN(1), true)
       .accounts({
         signer: user.publicKey,
         outputToken: usdc,
@@ -535,7 +1677,7 @@ describe("backyard-programs", () => {
         reserve: withdrawContext.reserve,
         tokenVault: withdrawContext.tokenVault,
         baseVaultAuthority: withdrawContext.baseVaultAuthority,
-        eventAuthority: withdrawContext.eventAuthority,
+        kaminoEventAuthority: withdrawContext.eventAuthority,
         sharesMint: withdrawContext.sharesMint,
         lendingMarket: withdrawContext.lendingMarket,
         lendingMarketAuthority: withdrawContext.lendingMarketAuthority,
@@ -552,24 +1694,109 @@ describe("backyard-programs", () => {
     const messageV0 = new TransactionMessage({
       payerKey: user.publicKey,
       recentBlockhash: (await connection.getLatestBlockhash()).blockhash,
-      instructions: [computeBudgetIx, instruction],
+      instructions: [ComputeBudgetProgram.setComputeUnitLimit({ units: 800_000 }), instruction],
     }).compileToV0Message();
 
     const transaction = new VersionedTransaction(messageV0);
     transaction.sign([user]);
 
     const tx = await connection.sendTransaction(transaction);
+    await connection.confirmTransaction(tx, "confirmed");
 
-    expect(tx).not.toBeNull();
+    expect(await connection.getAccountInfo(userLpAccount, "confirmed")).toBeNull();
+  });
 
-    const userLpBalanceAfter = await connection.getTokenAccountBalance(userLpAccount);
-    console.log("User LP balance after:", userLpBalanceAfter.value.uiAmount);
+  it("closes an empty vault and reclaims rent", async () => {
+    const depositContext = await getDepositContext({
+      asset: usdc,
+      signer: user.publicKey,
+      connection,
+    });
+    const vaultId = Keypair.generate().publicKey;
+    const vaultPda = PublicKey.findProgramAddressSync(
+      [Buffer.from("vault"), vaultId.toBuffer()],
+      program.programId
+    )[0];
+    const { vaultCount } = await program.account.config.fetch(configPda);
 
-    const userUsdcBalanceAfter = await connection.getTokenAccountBalance(userUsdcAccount);
-    console.log("User USDC balance after:", userUsdcBalanceAfter.value.uiAmount);
+    await program.methods
+      .createVault(vaultId, { jupiterLend: {} }, "Retired", "RET", "")
+      .accounts({
+        payer: protocolOwner.publicKey,
+        admin: protocolOwner.publicKey,
+        token: usdc,
+        externalLp: depositContext.fTokenMint,
+        market: depositContext.lending,
+        vaultEntry: vaultEntryPda(vaultCount),
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([protocolOwner])
+      .rpc();
 
-    expect(Number(userUsdcBalanceAfter.value.amount)).toBeGreaterThan(
-      Number(userUsdcBalanceBefore.value.amount)
+    const entry = await program.account.vaultEntry.fetch(vaultEntryPda(vaultCount));
+    expect(entry.vault.toBase58()).toEqual(vaultPda.toBase58());
+    expect(entry.protocol).toEqual({ jupiterLend: {} });
+    expect(entry.token.toBase58()).toEqual(usdc.toBase58());
+    expect(entry.status).toEqual({ active: {} });
+
+    // Setting fees creates the treasury, which is closed with the vault.
+    const lpToken = PublicKey.findProgramAddressSync(
+      [Buffer.from("internal_lp"), vaultId.toBuffer()],
+      program.programId
+    )[0];
+    const treasury = treasuryPda(vaultId);
+    const treasuryLpAta = getAssociatedTokenAddressSync(
+      lpToken,
+      treasury,
+      true,
+      TOKEN_2022_PROGRAM_ID
     );
+    const treasuryTokenAta = getAssociatedTokenAddressSync(usdc, treasury, true);
+    await program.methods
+      .setFees(vaultId, 0, 0, 0, 0)
+      .accounts({
+        feeManager: feeManager.publicKey,
+        market: depositContext.lending,
+        externalLp: depositContext.fTokenMint,
+        vaultLpAta: getAssociatedTokenAddressSync(
+          depositContext.fTokenMint,
+          vaultPda,
+          true,
+          TOKEN_PROGRAM_ID
+        ),
+        lpToken,
+        token: usdc,
+        tokenProgram: TOKEN_PROGRAM_ID,
+      })
+      .signers([feeManager])
+      .rpc();
+
+    const closeVault = (withTreasury: boolean) =>
+      program.methods
+        .closeVault(vaultId)
+        .accounts({
+          admin: protocolOwner.publicKey,
+          vaultEntry: vaultEntryPda(vaultCount),
+          token: usdc,
+          externalLp: depositContext.fTokenMint,
+          lpToken,
+          treasury: withTreasury ? treasury : null,
+          treasuryLpAta: withTreasury ? treasuryLpAta : null,
+          treasuryTokenAta: withTreasury ? treasuryTokenAta : null,
+          tokenProgram: TOKEN_PROGRAM_ID,
+        })
+        .signers([protocolOwner])
+        .rpc();
+
+    await expect(closeVault(false)).rejects.toThrow();
+    await closeVault(true);
+
+    expect(await connection.getAccountInfo(vaultPda)).toBeNull();
+    expect(await connection.getAccountInfo(treasury)).toBeNull();
+    expect(await connection.getAccountInfo(treasuryLpAta)).toBeNull();
+    expect(await connection.getAccountInfo(treasuryTokenAta)).toBeNull();
+    expect(
+      (await program.account.vaultEntry.fetch(vaultEntryPda(vaultCount))).status
+    ).toEqual({ closed: {} });
   });
 });
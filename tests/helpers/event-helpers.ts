@@ -0,0 +1,56 @@
+import { Idl, Program, utils } from "@coral-xyz/anchor";
+import { Connection } from "@solana/web3.js";
+
+/// Decodes the events a transaction emitted through `emit_cpi!`, which land in
+/// inner instructions targeting the program itself rather than in the logs.
+export async function getCpiEvents<T extends Idl>(
+  connection: Connection,
+  program: Program<T>,
+  signature: string
+) {
+  const tx = await connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+
+  if (!tx?.meta) {
+    throw new Error(`Transaction not found: ${signature}`);
+  }
+
+  const accountKeys = tx.transaction.message.getAccountKeys({
+    accountKeysFromLookups: tx.meta.loadedAddresses,
+  });
+
+  return (tx.meta.innerInstructions ?? [])
+    .flatMap((inner) => inner.instructions)
+    .filter((ix) => accountKeys.get(ix.programIdIndex)?.equals(program.programId))
+    .map((ix) =>
+      program.coder.events.decode(
+        utils.bytes.base64.encode(Buffer.from(utils.bytes.bs58.decode(ix.data)).subarray(8))
+      )
+    )
+    .filter((event) => event !== null);
+}
+
+/// Decodes the typed value a handler returned through `set_return_data`,
+/// read back from the `Program return:` log line of the transaction.
+export async function getReturnData<T extends Idl>(
+  connection: Connection,
+  program: Program<T>,
+  signature: string,
+  typeName: string
+) {
+  const tx = await connection.getTransaction(signature, {
+    commitment: "confirmed",
+    maxSupportedTransactionVersion: 0,
+  });
+
+  const prefix = `Program return: ${program.programId.toBase58()} `;
+  const log = tx?.meta?.logMessages?.find((line) => line.startsWith(prefix));
+
+  if (!log) {
+    throw new Error(`No return data in transaction: ${signature}`);
+  }
+
+  return program.coder.types.decode(typeName, Buffer.from(log.slice(prefix.length), "base64"));
+}